@@ -0,0 +1,156 @@
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Hitokoto分类
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Category {
+    pub code: String,                    // 分类代码，如 "a"
+    pub names: BTreeMap<String, String>, // 各语言的名称，键为语言代码
+    pub description: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>, // 迁移旧数据和提交时可识别的其他写法
+}
+
+// 分类及其条目数，用于 /categories 接口
+#[derive(Serialize)]
+pub struct CategoryWithCount {
+    #[serde(flatten)]
+    pub category: Category,
+    pub count: usize,
+}
+
+// 分类注册表
+pub struct CategoryRegistry {
+    categories: Vec<Category>,
+}
+
+// 无法识别的旧分类迁移到的默认分类（其他）
+pub const FALLBACK_CATEGORY: &str = "g";
+
+impl Category {
+    fn new(code: &str, zh: &str, en: &str, description: &str, aliases: &[&str]) -> Self {
+        let mut names = BTreeMap::new();
+        names.insert("zh".to_string(), zh.to_string());
+        names.insert("en".to_string(), en.to_string());
+        Category {
+            code: code.to_string(),
+            names,
+            description: Some(description.to_string()),
+            aliases: aliases.iter().map(|alias| alias.to_string()).collect(),
+        }
+    }
+
+    // 判断某个写法是否指向该分类（不区分大小写）
+    fn is_known_as(&self, value: &str) -> bool {
+        let value = value.trim().to_lowercase();
+        self.code == value
+            || self.names.values().any(|name| name.to_lowercase() == value)
+            || self
+                .aliases
+                .iter()
+                .any(|alias| alias.to_lowercase() == value)
+    }
+}
+
+impl Default for CategoryRegistry {
+    // 与 hitokoto.cn 一致的默认分类 a–l
+    fn default() -> Self {
+        let categories = vec![
+            Category::new(
+                "a",
+                "动画",
+                "Anime",
+                "来自动画作品",
+                &["anime", "animation"],
+            ),
+            Category::new("b", "漫画", "Comic", "来自漫画作品", &["comic", "manga"]),
+            Category::new("c", "游戏", "Game", "来自游戏作品", &["game"]),
+            Category::new(
+                "d",
+                "文学",
+                "Literature",
+                "来自文学作品",
+                &["literature", "novel", "小说"],
+            ),
+            Category::new("e", "原创", "Original", "用户原创内容", &["original"]),
+            Category::new(
+                "f",
+                "来自网络",
+                "Internet",
+                "来自网络",
+                &["internet", "网络"],
+            ),
+            Category::new(
+                "g",
+                "其他",
+                "Other",
+                "无法归入其他分类的内容",
+                &["other", "其它"],
+            ),
+            Category::new(
+                "h",
+                "影视",
+                "Video",
+                "来自电影或电视剧",
+                &["video", "movie", "film", "tv", "电影", "电视剧"],
+            ),
+            Category::new(
+                "i",
+                "诗词",
+                "Poem",
+                "来自古今诗词",
+                &["poem", "poetry", "诗", "词"],
+            ),
+            Category::new(
+                "j",
+                "网易云",
+                "NetEase Cloud Music",
+                "来自网易云音乐评论",
+                &["ncm", "netease", "music"],
+            ),
+            Category::new("k", "哲学", "Philosophy", "哲学思考", &["philosophy"]),
+            Category::new("l", "抖机灵", "Funny", "俏皮话", &["funny", "joke"]),
+        ];
+        CategoryRegistry { categories }
+    }
+}
+
+impl CategoryRegistry {
+    pub fn new(categories: Vec<Category>) -> Self {
+        CategoryRegistry { categories }
+    }
+
+    pub fn categories(&self) -> &[Category] {
+        &self.categories
+    }
+
+    // 根据代码获取分类
+    pub fn get(&self, code: &str) -> Option<&Category> {
+        self.categories.iter().find(|c| c.code == code)
+    }
+
+    // 将代码、名称或别名解析为规范的分类代码
+    pub fn resolve(&self, value: &str) -> Option<&str> {
+        self.get(value)
+            .or_else(|| self.categories.iter().find(|c| c.is_known_as(value)))
+            .map(|c| c.code.as_str())
+    }
+
+    // 校验提交的分类，返回规范的分类代码
+    pub fn validate(&self, value: &str) -> AppResult<String> {
+        self.resolve(value).map(str::to_string).ok_or_else(|| {
+            let codes: Vec<&str> = self.categories.iter().map(|c| c.code.as_str()).collect();
+            AppError::Validation(format!(
+                "未知的分类 \"{}\"，可选分类: {}",
+                value,
+                codes.join(", ")
+            ))
+        })
+    }
+
+    // 迁移旧数据中的分类值：可识别的写法转为规范代码，其余归入默认分类
+    pub fn migrate(&self, value: &str) -> String {
+        self.resolve(value).unwrap_or(FALLBACK_CATEGORY).to_string()
+    }
+}
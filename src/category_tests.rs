@@ -0,0 +1,24 @@
+use crate::{AppError, CategoryRegistry};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category_registry() {
+        // 测试分类代码、名称和别名的解析，以及未知分类的校验与迁移
+        let registry = CategoryRegistry::default();
+        assert_eq!(registry.categories().len(), 12);
+        assert_eq!(registry.resolve("a"), Some("a"));
+        assert_eq!(registry.resolve("A"), Some("a"));
+        assert_eq!(registry.resolve("anime"), Some("a"));
+        assert_eq!(registry.resolve("动画"), Some("a"));
+        assert_eq!(registry.validate("诗词").unwrap(), "i");
+
+        assert!(matches!(
+            registry.validate("unknown"),
+            Err(AppError::Validation(_))
+        ));
+        assert_eq!(registry.migrate("unknown"), "g");
+    }
+}
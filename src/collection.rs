@@ -1,5 +1,9 @@
 use crate::error::AppError;
+use crate::item::HitokotoItem;
+use crate::tag::normalize_tag;
+use rocket::FromFormField;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
@@ -10,8 +14,120 @@ pub struct Collection {
     pub title: String,
     pub description: Option<String>,
     pub user_id: u32,
-    pub hitokoto_ids: Vec<String>, // 存储 Hitokoto 的 UUID 引用
+    pub hitokoto_ids: Vec<CollectionEntry>, // 存储 Hitokoto 的 UUID 引用及批注
     pub created_at: u64,
+    #[serde(default)]
+    pub updated_at: u64, // 内容或批注最后一次变化的时间，旧数据为 0
+    #[serde(default)]
+    pub forked_from: Option<String>, // 复制来源文集的 ID
+    #[serde(default)]
+    pub fork_count: u32, // 被复制的次数
+    #[serde(default)]
+    pub query: Option<CollectionQuery>, // 智能文集的查询条件，存在时忽略 hitokoto_ids
+    #[serde(default)]
+    pub parent_id: Option<String>, // 父文集的 ID，为空时是顶层文集
+    #[serde(default)]
+    pub hidden: bool, // 因举报被隐藏，隐藏期间不能从中随机获取
+}
+
+// 文集中的一条记录：Hitokoto 的 UUID 及策展人的批注
+// 兼容旧格式：collection.json 中的纯字符串会被读取为无批注的记录
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[serde(from = "CollectionEntryRepr")]
+pub struct CollectionEntry {
+    pub uuid: String,
+    pub note: Option<String>,      // 收录理由等简短批注
+    pub highlight: Option<String>, // 高亮颜色
+    pub added_at: Option<u64>,     // 加入文集的时间，旧数据为空
+}
+
+// CollectionEntry 的反序列化格式
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CollectionEntryRepr {
+    Plain(String),
+    Full {
+        uuid: String,
+        #[serde(default)]
+        note: Option<String>,
+        #[serde(default)]
+        highlight: Option<String>,
+        #[serde(default)]
+        added_at: Option<u64>,
+    },
+}
+
+impl From<CollectionEntryRepr> for CollectionEntry {
+    fn from(repr: CollectionEntryRepr) -> Self {
+        match repr {
+            CollectionEntryRepr::Plain(uuid) => CollectionEntry {
+                uuid,
+                note: None,
+                highlight: None,
+                added_at: None,
+            },
+            CollectionEntryRepr::Full {
+                uuid,
+                note,
+                highlight,
+                added_at,
+            } => CollectionEntry {
+                uuid,
+                note,
+                highlight,
+                added_at,
+            },
+        }
+    }
+}
+
+impl CollectionEntry {
+    pub fn new(uuid: String) -> Self {
+        let added_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        CollectionEntry {
+            uuid,
+            note: None,
+            highlight: None,
+            added_at: Some(added_at),
+        }
+    }
+}
+
+// 修改文集条目批注的请求，字段为空时清除对应批注，user_id 为操作者
+#[derive(Deserialize)]
+pub struct AnnotateEntryRequest {
+    pub user_id: u32,
+    pub hitokoto_uuid: String,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub highlight: Option<String>,
+}
+
+// 智能文集的查询条件，所有条件之间为“与”的关系
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct CollectionQuery {
+    #[serde(rename = "type", default)]
+    pub item_type: Option<String>,
+    #[serde(default)]
+    pub from: Option<String>, // 来源包含该字符串
+    #[serde(default)]
+    pub from_who: Option<String>, // 作者包含该字符串
+    #[serde(default)]
+    pub user_id: Option<u32>, // 提交者
+    #[serde(default)]
+    pub min_length: Option<u32>,
+    #[serde(default)]
+    pub max_length: Option<u32>,
+    #[serde(default)]
+    pub created_after: Option<u64>,
+    #[serde(default)]
+    pub created_before: Option<u64>,
+    #[serde(default)]
+    pub tags: Vec<String>, // 须同时带有全部标签
 }
 
 // 创建新文集的请求
@@ -20,12 +136,66 @@ pub struct NewCollectionRequest {
     pub user_id: u32,
     pub title: String,
     pub description: Option<String>,
+    #[serde(default)]
+    pub query: Option<CollectionQuery>, // 提供时创建智能文集
+    #[serde(default)]
+    pub parent_id: Option<String>, // 提供时作为该文集的子文集
 }
 
-// 向文集添加 Hitokoto 的请求
+// 移动文集的请求，parent_id 为空时移动到顶层，user_id 为操作者
 #[derive(Deserialize)]
-pub struct AddToCollectionRequest {
-    pub hitokoto_uuid: String,
+pub struct MoveCollectionRequest {
+    pub user_id: u32,
+    pub parent_id: Option<String>,
+}
+
+// 删除含有子文集的文集时的处理方式
+#[derive(FromFormField, Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum DeleteMode {
+    // 连同所有子文集一起删除
+    Cascade,
+    // 子文集移动到顶层
+    #[default]
+    Unparent,
+}
+
+// 向文集添加或从文集移除 Hitokoto 的请求
+// 可以用 hitokoto_uuid 指定单个，也可以用 hitokoto_uuids 批量指定，user_id 为操作者
+#[derive(Deserialize)]
+pub struct CollectionEntriesRequest {
+    pub user_id: u32,
+    #[serde(default)]
+    pub hitokoto_uuid: Option<String>,
+    #[serde(default)]
+    pub hitokoto_uuids: Vec<String>,
+}
+
+// 批量添加的结果，skipped_duplicates 为已在文集中或在请求中重复而跳过的 UUID
+#[derive(Serialize, Debug, PartialEq)]
+pub struct AddToCollectionResult {
+    pub added: Vec<String>,
+    pub skipped_duplicates: Vec<String>,
+}
+
+// 复制文集的请求
+#[derive(Deserialize)]
+pub struct ForkCollectionRequest {
+    pub user_id: u32,
+}
+
+// 合并单个与批量字段，得到请求的全部 UUID
+fn merge_uuids(single: Option<String>, mut uuids: Vec<String>) -> Vec<String> {
+    if let Some(uuid) = single {
+        uuids.insert(0, uuid);
+    }
+    uuids
+}
+
+impl CollectionEntriesRequest {
+    pub fn into_uuids(self) -> Vec<String> {
+        merge_uuids(self.hitokoto_uuid, self.hitokoto_uuids)
+    }
 }
 
 impl Collection {
@@ -51,23 +221,226 @@ impl Collection {
             user_id,
             hitokoto_ids: Vec::new(),
             created_at,
+            updated_at: created_at,
+            forked_from: None,
+            fork_count: 0,
+            query: None,
+            parent_id: None,
+            hidden: false,
         })
     }
 
+    // 创建由查询条件决定内容的智能文集
+    pub fn new_smart(
+        title: String,
+        description: Option<String>,
+        user_id: u32,
+        query: CollectionQuery,
+    ) -> Result<Self, AppError> {
+        query.validate()?;
+        let mut collection = Collection::new(title, description, user_id)?;
+        collection.query = Some(query);
+        Ok(collection)
+    }
+
+    // 是否为智能文集
+    pub fn is_smart(&self) -> bool {
+        self.query.is_some()
+    }
+
+    // 记录文集在此刻被修改
+    pub fn touch(&mut self) {
+        self.updated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+    }
+
+    // 将智能文集转换为静态文集，uuids 为当前查询结果
+    pub fn snapshot(&mut self, uuids: Vec<String>) {
+        self.hitokoto_ids = uuids.into_iter().map(CollectionEntry::new).collect();
+        self.query = None;
+    }
+
+    // 文集中所有 Hitokoto 的 UUID
+    pub fn uuids(&self) -> Vec<String> {
+        self.hitokoto_ids
+            .iter()
+            .map(|entry| entry.uuid.clone())
+            .collect()
+    }
+
+    // 文集中是否包含指定 Hitokoto
+    pub fn contains_hitokoto(&self, hitokoto_uuid: &str) -> bool {
+        self.hitokoto_ids
+            .iter()
+            .any(|entry| entry.uuid == hitokoto_uuid)
+    }
+
+    // 修改文集条目的批注，条目不存在时返回错误
+    pub fn annotate(
+        &mut self,
+        hitokoto_uuid: &str,
+        note: Option<String>,
+        highlight: Option<String>,
+    ) -> Result<CollectionEntry, AppError> {
+        if note.as_ref().is_some_and(|n| n.chars().count() > 200) {
+            return Err(AppError::Validation("批注不能超过200个字符".to_string()));
+        }
+        if highlight.as_ref().is_some_and(|h| h.chars().count() > 32) {
+            return Err(AppError::Validation("高亮颜色不能超过32个字符".to_string()));
+        }
+
+        let entry = self
+            .hitokoto_ids
+            .iter_mut()
+            .find(|entry| entry.uuid == hitokoto_uuid)
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Hitokoto UUID {} 不在文集中", hitokoto_uuid))
+            })?;
+        entry.note = note.filter(|n| !n.trim().is_empty());
+        entry.highlight = highlight.filter(|h| !h.trim().is_empty());
+        Ok(entry.clone())
+    }
+
+    // 为指定用户复制一份文集，复制品记录来源文集的 ID
+    pub fn fork(&self, user_id: u32) -> Result<Self, AppError> {
+        let mut forked = Collection::new(self.title.clone(), self.description.clone(), user_id)?;
+        forked.hitokoto_ids = self.hitokoto_ids.clone();
+        forked.query = self.query.clone();
+        forked.forked_from = Some(self.collection_id.clone());
+        Ok(forked)
+    }
+
     // 添加 Hitokoto UUID 到文集
     pub fn add_hitokoto(&mut self, hitokoto_uuid: String) {
-        if !self.hitokoto_ids.contains(&hitokoto_uuid) {
-            self.hitokoto_ids.push(hitokoto_uuid);
+        if !self.contains_hitokoto(&hitokoto_uuid) {
+            self.hitokoto_ids.push(CollectionEntry::new(hitokoto_uuid));
         }
     }
 
     // 从文集中移除 Hitokoto UUID
     pub fn remove_hitokoto(&mut self, hitokoto_uuid: &str) -> bool {
-        if let Some(pos) = self.hitokoto_ids.iter().position(|id| id == hitokoto_uuid) {
+        if let Some(pos) = self
+            .hitokoto_ids
+            .iter()
+            .position(|entry| entry.uuid == hitokoto_uuid)
+        {
             self.hitokoto_ids.remove(pos);
             true
         } else {
             false
         }
     }
+
+    // 批量添加前的筛选，将 UUID 分为尚未在文集中的与重复的两组
+    // 请求中重复的只添加第一个，每个跳过的 UUID 只列出一次
+    pub fn partition_uuids(&self, uuids: &[String]) -> AddToCollectionResult {
+        let mut added: Vec<String> = Vec::new();
+        let mut skipped_duplicates: Vec<String> = Vec::new();
+        for uuid in uuids {
+            if !self.contains_hitokoto(uuid) && !added.contains(uuid) {
+                added.push(uuid.clone());
+            } else if !skipped_duplicates.contains(uuid) {
+                skipped_duplicates.push(uuid.clone());
+            }
+        }
+        AddToCollectionResult {
+            added,
+            skipped_duplicates,
+        }
+    }
+
+    // 批量移除前的检查，返回请求中重复出现或文集中不存在的 UUID
+    pub fn absent_uuids(&self, uuids: &[String]) -> Vec<String> {
+        let mut absent = Vec::new();
+        for (i, uuid) in uuids.iter().enumerate() {
+            let invalid = uuids[..i].contains(uuid) || !self.contains_hitokoto(uuid);
+            if invalid && !absent.contains(uuid) {
+                absent.push(uuid.clone());
+            }
+        }
+        absent
+    }
+}
+
+// 检查将文集移动到 new_parent 之下是否会形成环
+pub fn would_create_cycle(
+    collections: &HashMap<String, Collection>,
+    collection_id: &str,
+    new_parent: &str,
+) -> bool {
+    let mut current = Some(new_parent.to_string());
+    while let Some(id) = current {
+        if id == collection_id {
+            return true;
+        }
+        current = collections.get(&id).and_then(|c| c.parent_id.clone());
+    }
+    false
+}
+
+// 获取文集的所有后代文集 ID（不含自身）
+pub fn descendant_ids(
+    collections: &HashMap<String, Collection>,
+    collection_id: &str,
+) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut pending = vec![collection_id.to_string()];
+    while let Some(parent) = pending.pop() {
+        for child in collections
+            .values()
+            .filter(|c| c.parent_id.as_deref() == Some(parent.as_str()))
+        {
+            if !result.contains(&child.collection_id) {
+                result.push(child.collection_id.clone());
+                pending.push(child.collection_id.clone());
+            }
+        }
+    }
+    result
+}
+
+impl CollectionQuery {
+    // 检查查询条件是否合法
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let (Some(min), Some(max)) = (self.min_length, self.max_length)
+            && min > max
+        {
+            return Err(AppError::Collection(
+                "查询条件的最小长度不能大于最大长度".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    // 判断 Hitokoto 是否满足查询条件
+    pub fn matches(&self, item: &HitokotoItem) -> bool {
+        self.item_type
+            .as_ref()
+            .is_none_or(|item_type| &item.item_type == item_type)
+            && self
+                .from
+                .as_ref()
+                .is_none_or(|from| item.from.contains(from.as_str()))
+            && self.from_who.as_ref().is_none_or(|from_who| {
+                item.from_who
+                    .as_ref()
+                    .is_some_and(|who| who.contains(from_who.as_str()))
+            })
+            && self.user_id.is_none_or(|user_id| item.user_id == user_id)
+            && self.min_length.is_none_or(|min| item.length >= min)
+            && self.max_length.is_none_or(|max| item.length <= max)
+            && self
+                .created_after
+                .is_none_or(|created_after| item.created_at > created_after)
+            && self
+                .created_before
+                .is_none_or(|created_before| item.created_at < created_before)
+            && self
+                .tags
+                .iter()
+                .filter_map(|tag| normalize_tag(tag))
+                .all(|tag| item.tags.contains(&tag))
+    }
 }
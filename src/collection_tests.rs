@@ -0,0 +1,170 @@
+use crate::collection::{AddToCollectionResult, descendant_ids, would_create_cycle};
+use crate::{Collection, CollectionQuery, HitokotoItem};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collection_fork() {
+        // 测试复制文集会保留内容并记录来源
+        let mut source =
+            Collection::new("原文集".to_string(), Some("描述".to_string()), 1).unwrap();
+        source.add_hitokoto("uuid-1".to_string());
+        source.add_hitokoto("uuid-2".to_string());
+
+        let forked = source.fork(2).unwrap();
+        assert_ne!(forked.collection_id, source.collection_id);
+        assert_eq!(forked.user_id, 2);
+        assert_eq!(forked.title, source.title);
+        assert_eq!(forked.hitokoto_ids, source.hitokoto_ids);
+        assert_eq!(
+            forked.forked_from.as_deref(),
+            Some(source.collection_id.as_str())
+        );
+        assert_eq!(forked.fork_count, 0);
+    }
+
+    #[test]
+    fn test_collection_load_without_fork_fields() {
+        // 测试旧格式的文集数据仍能正常加载
+        let json = r#"{
+            "collection_id": "c1",
+            "title": "旧文集",
+            "description": null,
+            "user_id": 1,
+            "hitokoto_ids": ["uuid-1"],
+            "created_at": 1753356305
+        }"#;
+        let collection: Collection = serde_json::from_str(json).unwrap();
+        assert!(collection.forked_from.is_none());
+        assert_eq!(collection.uuids(), vec!["uuid-1".to_string()]);
+        assert!(collection.hitokoto_ids[0].note.is_none());
+        assert!(collection.hitokoto_ids[0].added_at.is_none());
+        assert_eq!(collection.fork_count, 0);
+    }
+
+    #[test]
+    fn test_smart_collection_query() {
+        // 测试智能文集的查询条件匹配
+        let item = HitokotoItem::new(
+            "人类的悲欢并不相通".to_string(),
+            "d".to_string(),
+            "小杂感".to_string(),
+            Some("鲁迅".to_string()),
+            "tester".to_string(),
+            1,
+        );
+
+        let query = CollectionQuery {
+            item_type: Some("d".to_string()),
+            from_who: Some("鲁迅".to_string()),
+            max_length: Some(20),
+            ..Default::default()
+        };
+        assert!(query.matches(&item));
+
+        let query = CollectionQuery {
+            user_id: Some(2),
+            ..Default::default()
+        };
+        assert!(!query.matches(&item));
+
+        let invalid = CollectionQuery {
+            min_length: Some(10),
+            max_length: Some(5),
+            ..Default::default()
+        };
+        assert!(Collection::new_smart("智能".to_string(), None, 1, invalid).is_err());
+    }
+
+    #[test]
+    fn test_smart_collection_snapshot() {
+        // 测试智能文集转换为静态文集
+        let mut collection =
+            Collection::new_smart("智能".to_string(), None, 1, CollectionQuery::default()).unwrap();
+        assert!(collection.is_smart());
+
+        collection.snapshot(vec!["uuid-1".to_string()]);
+        assert!(!collection.is_smart());
+        assert_eq!(collection.uuids(), vec!["uuid-1".to_string()]);
+    }
+
+    #[test]
+    fn test_nested_collection_cycle_detection() {
+        // 测试文集树的环检测与后代查找: a -> b -> c
+        let a = Collection::new("a".to_string(), None, 1).unwrap();
+        let mut b = Collection::new("b".to_string(), None, 1).unwrap();
+        let mut c = Collection::new("c".to_string(), None, 1).unwrap();
+        b.parent_id = Some(a.collection_id.clone());
+        c.parent_id = Some(b.collection_id.clone());
+        let (a_id, b_id, c_id) = (
+            a.collection_id.clone(),
+            b.collection_id.clone(),
+            c.collection_id.clone(),
+        );
+
+        let collections: HashMap<String, Collection> = [a, b, c]
+            .into_iter()
+            .map(|collection| (collection.collection_id.clone(), collection))
+            .collect();
+
+        assert!(would_create_cycle(&collections, &a_id, &c_id));
+        assert!(would_create_cycle(&collections, &a_id, &a_id));
+        assert!(!would_create_cycle(&collections, &c_id, &a_id));
+
+        let mut descendants = descendant_ids(&collections, &a_id);
+        descendants.sort();
+        let mut expected = vec![b_id, c_id];
+        expected.sort();
+        assert_eq!(descendants, expected);
+    }
+
+    #[test]
+    fn test_bulk_uuid_checks() {
+        // 测试批量添加前的去重与批量移除前的缺失检查
+        let mut collection = Collection::new("批量".to_string(), None, 1).unwrap();
+        collection.add_hitokoto("uuid-1".to_string());
+
+        let request = vec![
+            "uuid-1".to_string(),
+            "uuid-2".to_string(),
+            "uuid-2".to_string(),
+            "uuid-3".to_string(),
+        ];
+        assert_eq!(
+            collection.partition_uuids(&request),
+            AddToCollectionResult {
+                added: vec!["uuid-2".to_string(), "uuid-3".to_string()],
+                skipped_duplicates: vec!["uuid-1".to_string(), "uuid-2".to_string()],
+            }
+        );
+        assert_eq!(
+            collection.absent_uuids(&request),
+            vec!["uuid-2".to_string(), "uuid-3".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_collection_entry_annotation() {
+        // 测试文集条目批注的修改与序列化往返
+        let mut collection = Collection::new("批注".to_string(), None, 1).unwrap();
+        collection.add_hitokoto("uuid-1".to_string());
+
+        let entry = collection
+            .annotate(
+                "uuid-1",
+                Some("开篇".to_string()),
+                Some("#ffcc00".to_string()),
+            )
+            .unwrap();
+        assert_eq!(entry.note.as_deref(), Some("开篇"));
+        assert!(entry.added_at.is_some());
+        assert!(collection.annotate("uuid-2", None, None).is_err());
+
+        let json = serde_json::to_string(&collection).unwrap();
+        let loaded: Collection = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.hitokoto_ids, collection.hitokoto_ids);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::strategy::SelectionStrategy;
+use serde::Deserialize;
+
+// 应用配置，从 Rocket.toml 的 [default.pencil] 等段读取，缺省字段使用默认值
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct AppConfig {
+    pub validation: ValidationConfig,
+    pub svg: SvgConfig,
+    pub webhook: WebhookConfig,
+    // 敏感词词典文件路径
+    pub sensitive_words_path: String,
+    // 待处理举报达到该数量时自动隐藏，0 表示不隐藏
+    pub report_hide_threshold: usize,
+    // 每日一言划分日期的时区，相对 UTC 的分钟数
+    pub daily_utc_offset_minutes: i32,
+    // 不重复随机的客户端状态闲置多久后清除
+    pub client_state_ttl_secs: u64,
+    // 最多同时保存多少个客户端的不重复随机状态
+    pub client_state_max: usize,
+    // 全局随机数种子，为空时使用系统熵
+    pub rng_seed: Option<u64>,
+    // 请求未指定 strategy 时的随机选取策略
+    pub default_strategy: SelectionStrategy,
+    // recent 策略中条目权重衰减一半所需的天数
+    pub recency_half_life_days: f64,
+    // /get 一次最多返回的条目数
+    pub max_random_count: usize,
+    // 服务对外的网址，用于订阅源中的链接
+    pub public_url: String,
+    // 订阅源中的条目数
+    pub feed_size: usize,
+    // 事件流保存的最近事件数，用于断线续传
+    pub event_backlog_size: usize,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            validation: ValidationConfig::default(),
+            svg: SvgConfig::default(),
+            webhook: WebhookConfig::default(),
+            sensitive_words_path: "sensitive_words.txt".to_string(),
+            report_hide_threshold: 3,
+            daily_utc_offset_minutes: 480,
+            client_state_ttl_secs: 86_400,
+            client_state_max: 100_000,
+            rng_seed: None,
+            default_strategy: SelectionStrategy::Uniform,
+            recency_half_life_days: 30.0,
+            max_random_count: 20,
+            public_url: "http://localhost:8000".to_string(),
+            feed_size: 20,
+            event_backlog_size: 256,
+        }
+    }
+}
+
+// 提交Hitokoto时的内容校验规则
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ValidationConfig {
+    pub min_length: usize,          // hitokoto 最少字符数
+    pub max_length: usize,          // hitokoto 最多字符数
+    pub require_from: bool,         // 是否必须填写来源
+    pub max_from_length: usize,     // from 最多字符数
+    pub max_from_who_length: usize, // from_who 最多字符数
+    pub disallowed_chars: String,   // 禁止出现的字符，控制字符始终禁止
+    pub allow_urls: bool,           // 是否允许内容中包含网址
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        ValidationConfig {
+            min_length: 1,
+            max_length: 500,
+            require_from: true,
+            max_from_length: 100,
+            max_from_who_length: 50,
+            disallowed_chars: "<>".to_string(),
+            allow_urls: false,
+        }
+    }
+}
+
+// /get?encode=svg 输出的卡片样式
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct SvgConfig {
+    pub width: u32,          // 卡片宽度（像素），高度随折行后的行数变化
+    pub padding: u32,        // 内边距（像素）
+    pub font_size: u32,      // 正文字号（像素），出处为其 0.8 倍
+    pub line_height: f64,    // 行高，为字号的倍数
+    pub font_family: String, // 字体，按 CSS font-family 的写法
+    pub color: String,       // 正文颜色
+    pub from_color: String,  // 出处颜色
+    pub background: String,  // 背景颜色
+    pub border_radius: u32,  // 圆角半径（像素）
+}
+
+impl Default for SvgConfig {
+    fn default() -> Self {
+        SvgConfig {
+            width: 480,
+            padding: 24,
+            font_size: 18,
+            line_height: 1.6,
+            font_family: "'Noto Serif SC', 'Source Han Serif SC', 'Songti SC', serif".to_string(),
+            color: "#333333".to_string(),
+            from_color: "#888888".to_string(),
+            background: "#ffffff".to_string(),
+            border_radius: 8,
+        }
+    }
+}
+
+// 回调投递的重试策略
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub max_attempts: u32,    // 每次投递最多尝试的次数，用完后标记为失败
+    pub retry_base_secs: u64, // 第一次失败后的重试间隔（秒），之后每次翻倍
+    pub retry_max_secs: u64,  // 重试间隔的上限（秒）
+    pub timeout_secs: u64,    // 单次请求的超时时间（秒）
+    pub log_size: usize,      // 保留的已结束投递记录数
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        WebhookConfig {
+            max_attempts: 6,
+            retry_base_secs: 10,
+            retry_max_secs: 3600,
+            timeout_secs: 10,
+            log_size: 500,
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::item::HitokotoItem;
+use rocket::FromForm;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// 某一天的每日一言
+#[derive(Deserialize, Serialize, Clone)]
+pub struct DailyPick {
+    pub date: String, // YYYY-MM-DD
+    pub uuid: String,
+    pub pinned: bool, // 是否由管理员预先指定
+    pub picked_at: u64,
+}
+
+// 每日一言的选取记录与管理员预先指定的条目，键为日期
+#[derive(Deserialize, Serialize, Default)]
+pub struct DailyArchive {
+    pub picks: BTreeMap<String, DailyPick>,
+    pub pins: BTreeMap<String, String>, // 日期 -> Hitokoto UUID
+}
+
+// 每日一言接口的响应
+#[derive(Serialize)]
+pub struct DailyResponse {
+    pub date: String,
+    pub pinned: bool,
+    pub item: HitokotoItem,
+}
+
+// 历史每日一言，条目已被删除或隐藏时 item 为空
+#[derive(Serialize)]
+pub struct DailyArchiveEntry {
+    pub date: String,
+    pub pinned: bool,
+    pub item: Option<HitokotoItem>,
+}
+
+// 指定某天的每日一言的请求，user_id 为操作者
+#[derive(Deserialize)]
+pub struct DailyPinRequest {
+    pub user_id: u32,
+    pub date: String,
+    pub uuid: String,
+}
+
+// 历史每日一言的查询参数
+#[derive(FromForm)]
+pub struct DailyArchiveQuery {
+    pub before: Option<String>, // 只列出该日期之前的记录，用于翻页
+    pub limit: Option<usize>,   // 默认 30，最大 365
+}
+
+// 自 1970-01-01 起的天数转为公历日期
+// 算法见 Howard Hinnant, "chrono-Compatible Low-Level Date Algorithms"
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// 公历日期转为自 1970-01-01 起的天数
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (i64::from(month) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// Unix 时间戳在给定时区（相对 UTC 的分钟数）下的日期，格式为 YYYY-MM-DD
+pub fn date_for_timestamp(timestamp: u64, utc_offset_minutes: i32) -> String {
+    let local = timestamp as i64 + i64::from(utc_offset_minutes) * 60;
+    let (year, month, day) = civil_from_days(local.div_euclid(86_400));
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// 校验并规范化 YYYY-MM-DD 格式的日期，无效时返回 None
+pub fn parse_date(date: &str) -> Option<String> {
+    let mut parts = date.trim().splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || day == 0 {
+        return None;
+    }
+    // 往返转换一次，排除 2 月 30 日之类不存在的日期
+    let (y, m, d) = civil_from_days(days_from_civil(year, month, day));
+    (y == year && m == month && d == day).then(|| format!("{:04}-{:02}-{:02}", year, month, day))
+}
+
+// 稳定的 64 位哈希（FNV-1a），不依赖标准库哈希的实现，可用于生成随机种子
+pub fn stable_hash(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// splitmix64 的混合函数，使只差一个字符的输入得到差异明显的哈希值
+pub fn mix(hash: u64) -> u64 {
+    let mut z = hash;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// 以日期的哈希值从候选条目中确定性地选取一条
+// 候选按创建时间和 UUID 排序，保证与数据的存储顺序无关
+// 不使用随机数生成器，选取结果不随依赖库的版本变化
+pub fn pick_for_date<'a>(date: &str, candidates: &[&'a HitokotoItem]) -> Option<&'a HitokotoItem> {
+    let mut sorted = candidates.to_vec();
+    sorted.sort_by(|a, b| {
+        a.created_at
+            .cmp(&b.created_at)
+            .then_with(|| a.uuid.cmp(&b.uuid))
+    });
+    if sorted.is_empty() {
+        return None;
+    }
+    let index = mix(stable_hash(date.bytes())) % sorted.len() as u64;
+    Some(sorted[index as usize])
+}
@@ -0,0 +1,49 @@
+use crate::HitokotoItem;
+use crate::daily::{date_for_timestamp, parse_date, pick_for_date};
+use crate::test_support::sample_items;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_for_timestamp_and_parse() {
+        // 2024-02-29 16:30:00 UTC
+        let timestamp = 1_709_224_200;
+        assert_eq!(date_for_timestamp(timestamp, 0), "2024-02-29");
+        // 东八区已是第二天，西五区仍是当天
+        assert_eq!(date_for_timestamp(timestamp, 480), "2024-03-01");
+        assert_eq!(date_for_timestamp(timestamp, -300), "2024-02-29");
+        assert_eq!(date_for_timestamp(0, -60), "1969-12-31");
+
+        assert_eq!(parse_date("2024-2-29").as_deref(), Some("2024-02-29"));
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("昨天"), None);
+    }
+
+    #[test]
+    fn test_pick_for_date_is_deterministic() {
+        let mut items = sample_items(20);
+        for (i, item) in items.iter_mut().enumerate() {
+            item.created_at = i as u64;
+        }
+        let candidates: Vec<&HitokotoItem> = items.iter().collect();
+        let reversed: Vec<&HitokotoItem> = items.iter().rev().collect();
+
+        // 同一日期的选取与候选顺序无关
+        let pick = pick_for_date("2024-03-01", &candidates).unwrap();
+        assert_eq!(
+            pick_for_date("2024-03-01", &reversed).unwrap().uuid,
+            pick.uuid
+        );
+
+        // 选取结果固定，不随依赖库的版本变化；相邻日期的选取分散
+        let picks: Vec<u64> = (1..=10)
+            .map(|day| format!("2024-03-{:02}", day))
+            .map(|date| pick_for_date(&date, &candidates).unwrap().created_at)
+            .collect();
+        assert_eq!(picks, [15, 14, 9, 10, 5, 19, 2, 2, 12, 11]);
+        assert!(pick_for_date("2024-03-01", &[]).is_none());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::item::{HitokotoItem, ItemStatus};
+use std::collections::{HashMap, HashSet};
+
+// 相似度达到该值时视为近似重复
+pub const NEAR_DUPLICATE_THRESHOLD: f64 = 0.8;
+
+// 分片（shingle）的字符数
+const SHINGLE_SIZE: usize = 3;
+
+// 规范化文本：全角字符转半角、转为小写，去除标点和空白
+pub fn normalize_text(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{3000}' => ' ',
+            '\u{ff01}'..='\u{ff5e}' => char::from_u32(c as u32 - 0xfee0).unwrap_or(c),
+            _ => c,
+        })
+        .flat_map(char::to_lowercase)
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+// 将规范化文本切分为连续字符分片，文本短于分片长度时整体作为一个分片
+pub fn shingles(normalized: &str) -> HashSet<String> {
+    let chars: Vec<char> = normalized.chars().collect();
+    if chars.len() <= SHINGLE_SIZE {
+        return std::iter::once(normalized.to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+    }
+    chars
+        .windows(SHINGLE_SIZE)
+        .map(|window| window.iter().collect())
+        .collect()
+}
+
+// 两个分片集合的 Jaccard 相似度
+pub fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.intersection(b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+// 重复检测索引
+// exact 以规范化文本为键用于精确查重，postings 为分片倒排表用于快速找出近似候选
+#[derive(Default)]
+pub struct DuplicateIndex {
+    exact: HashMap<String, String>,             // 规范化文本 -> UUID
+    shingles: HashMap<String, HashSet<String>>, // UUID -> 分片集合
+    postings: HashMap<String, HashSet<String>>, // 分片 -> UUID
+}
+
+impl DuplicateIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 从全部数据重建索引，被拒绝的条目不参与查重，相同内容可以重新提交
+    pub fn build(items: &[HitokotoItem]) -> Self {
+        let mut index = Self::new();
+        for item in items {
+            if item.status != ItemStatus::Rejected {
+                index.insert(&item.uuid, &item.hitokoto);
+            }
+        }
+        index
+    }
+
+    // 条目内容或状态变化后同步索引
+    pub fn update(&mut self, before: &HitokotoItem, after: &HitokotoItem) {
+        self.remove(&before.uuid, &before.hitokoto);
+        if after.status != ItemStatus::Rejected {
+            self.insert(&after.uuid, &after.hitokoto);
+        }
+    }
+
+    // 添加条目，规范化后为空的文本不参与查重
+    pub fn insert(&mut self, uuid: &str, text: &str) {
+        let normalized = normalize_text(text);
+        if normalized.is_empty() {
+            return;
+        }
+        let set = shingles(&normalized);
+        for shingle in &set {
+            self.postings
+                .entry(shingle.clone())
+                .or_default()
+                .insert(uuid.to_string());
+        }
+        self.shingles.insert(uuid.to_string(), set);
+        self.exact
+            .entry(normalized)
+            .or_insert_with(|| uuid.to_string());
+    }
+
+    // 移除条目
+    pub fn remove(&mut self, uuid: &str, text: &str) {
+        let normalized = normalize_text(text);
+        if self.exact.get(&normalized).is_some_and(|u| u == uuid) {
+            self.exact.remove(&normalized);
+        }
+        if let Some(set) = self.shingles.remove(uuid) {
+            for shingle in set {
+                if let Some(uuids) = self.postings.get_mut(&shingle) {
+                    uuids.remove(uuid);
+                    if uuids.is_empty() {
+                        self.postings.remove(&shingle);
+                    }
+                }
+            }
+        }
+    }
+
+    // 查找规范化后完全相同的条目
+    pub fn find_exact(&self, text: &str) -> Option<&str> {
+        self.exact.get(&normalize_text(text)).map(String::as_str)
+    }
+
+    // 查找相似度不低于阈值的条目，按相似度降序返回 UUID
+    pub fn find_similar(&self, text: &str, threshold: f64) -> Vec<String> {
+        let set = shingles(&normalize_text(text));
+        let candidates: HashSet<&String> = set
+            .iter()
+            .filter_map(|shingle| self.postings.get(shingle))
+            .flatten()
+            .collect();
+
+        let mut similar: Vec<(f64, String)> = candidates
+            .into_iter()
+            .filter_map(|uuid| {
+                let score = jaccard(&set, self.shingles.get(uuid)?);
+                (score >= threshold).then(|| (score, uuid.clone()))
+            })
+            .collect();
+        similar.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        similar.into_iter().map(|(_, uuid)| uuid).collect()
+    }
+}
@@ -0,0 +1,69 @@
+use crate::dedup::{DuplicateIndex, NEAR_DUPLICATE_THRESHOLD, normalize_text};
+use crate::item::ItemStatus;
+use crate::test_support::sample_item;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_detection() {
+        // 测试全角/半角、标点和空白差异的精确查重，以及近似重复检测
+        assert_eq!(normalize_text("Ｈｅｌｌｏ，　World！"), "helloworld");
+
+        let mut index = DuplicateIndex::new();
+        index.insert("uuid-1", "给岁月以文明，而不是给文明以岁月。");
+
+        assert_eq!(
+            index.find_exact("给岁月以文明 而不是给文明以岁月"),
+            Some("uuid-1")
+        );
+        assert!(index.find_exact("给岁月以文明").is_none());
+
+        let similar = index.find_similar(
+            "给岁月以文明，而不是给文明以岁月啊",
+            NEAR_DUPLICATE_THRESHOLD,
+        );
+        assert_eq!(similar, vec!["uuid-1".to_string()]);
+        assert!(
+            index
+                .find_similar("弱小和无知不是生存的障碍", NEAR_DUPLICATE_THRESHOLD)
+                .is_empty()
+        );
+
+        index.remove("uuid-1", "给岁月以文明，而不是给文明以岁月。");
+        assert!(
+            index
+                .find_exact("给岁月以文明，而不是给文明以岁月。")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_rejected_items_leave_duplicate_index() {
+        // 测试被拒绝的条目不再阻止相同内容重新提交
+        let mut item = sample_item("给岁月以文明，而不是给文明以岁月。");
+        let mut index = DuplicateIndex::build(std::slice::from_ref(&item));
+        assert!(index.find_exact(&item.hitokoto).is_some());
+
+        let before = item.clone();
+        item.status = ItemStatus::Rejected;
+        index.update(&before, &item);
+        assert!(index.find_exact(&item.hitokoto).is_none());
+        assert!(
+            index
+                .find_similar(&item.hitokoto, NEAR_DUPLICATE_THRESHOLD)
+                .is_empty()
+        );
+        assert!(
+            DuplicateIndex::build(std::slice::from_ref(&item))
+                .find_exact(&item.hitokoto)
+                .is_none()
+        );
+
+        let before = item.clone();
+        item.status = ItemStatus::Approved;
+        index.update(&before, &item);
+        assert_eq!(index.find_exact(&item.hitokoto), Some(item.uuid.as_str()));
+    }
+}
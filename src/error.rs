@@ -23,6 +23,35 @@ pub enum AppError {
     NotFound(String),
     /// 资源已存在错误
     AlreadyExists(String),
+    /// 权限不足错误
+    Forbidden(String),
+    /// 字段级验证错误，一次返回全部问题
+    InvalidFields(Vec<FieldError>),
+}
+
+/// 单个字段的验证错误
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        FieldError {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// 将字段错误列表拼接为可读的错误信息
+fn join_field_errors(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("{}: {}", e.field, e.message))
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 impl fmt::Display for AppError {
@@ -37,6 +66,10 @@ impl fmt::Display for AppError {
             AppError::Validation(msg) => write!(f, "验证错误: {}", msg),
             AppError::NotFound(msg) => write!(f, "资源不存在: {}", msg),
             AppError::AlreadyExists(msg) => write!(f, "资源已存在: {}", msg),
+            AppError::Forbidden(msg) => write!(f, "权限不足: {}", msg),
+            AppError::InvalidFields(errors) => {
+                write!(f, "验证错误: {}", join_field_errors(errors))
+            }
         }
     }
 }
@@ -48,6 +81,8 @@ impl Error for AppError {}
 pub struct ErrorResponse {
     pub error: String,
     pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<FieldError>>, // 字段级验证错误详情
 }
 
 impl AppError {
@@ -72,9 +107,22 @@ impl AppError {
             AppError::AlreadyExists(msg) => {
                 (format!("资源已存在: {}", msg), "ALREADY_EXISTS".to_string())
             }
+            AppError::Forbidden(msg) => (format!("权限不足: {}", msg), "FORBIDDEN".to_string()),
+            AppError::InvalidFields(errors) => (
+                format!("验证错误: {}", join_field_errors(errors)),
+                "VALIDATION_ERROR".to_string(),
+            ),
+        };
+        let fields = match self {
+            AppError::InvalidFields(errors) => Some(errors.clone()),
+            _ => None,
         };
 
-        ErrorResponse { error, code }
+        ErrorResponse {
+            error,
+            code,
+            fields,
+        }
     }
 
     /// 获取HTTP状态码
@@ -83,7 +131,8 @@ impl AppError {
         match self {
             AppError::NotFound(_) => Status::NotFound,
             AppError::AlreadyExists(_) => Status::Conflict,
-            AppError::Validation(_) => Status::BadRequest,
+            AppError::Forbidden(_) => Status::Forbidden,
+            AppError::Validation(_) | AppError::InvalidFields(_) => Status::BadRequest,
             AppError::User(_) | AppError::Collection(_) | AppError::Hitokoto(_) => {
                 Status::BadRequest
             }
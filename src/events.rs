@@ -0,0 +1,249 @@
+use crate::item::{HitokotoItem, ItemStatus};
+use crate::revision::diff_items;
+use rocket::FromForm;
+use rocket::request::{self, FromRequest, Request};
+use rocket::response::stream::Event;
+use serde::{Serialize, Serializer};
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+// Hitokoto事件的类型
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemEventKind {
+    Created,  // 提交成功
+    Approved, // 通过审核，开始公开显示
+    Edited,   // 内容、标签或状态被修改
+    Deleted,  // 被拒绝或因举报隐藏，不再公开显示
+}
+
+impl ItemEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ItemEventKind::Created => "created",
+            ItemEventKind::Approved => "approved",
+            ItemEventKind::Edited => "edited",
+            ItemEventKind::Deleted => "deleted",
+        }
+    }
+
+    // 按修改前后的条目判断事件类型，before 为空表示新提交，没有变化时返回 None
+    pub fn classify(before: Option<&HitokotoItem>, after: &HitokotoItem) -> Option<Self> {
+        let Some(before) = before else {
+            return Some(ItemEventKind::Created);
+        };
+        if !before.is_public() && after.is_public() {
+            Some(ItemEventKind::Approved)
+        } else if (before.is_public() && !after.is_public())
+            || (before.status != ItemStatus::Rejected && after.status == ItemStatus::Rejected)
+        {
+            Some(ItemEventKind::Deleted)
+        } else if diff_items(before, after).is_empty() {
+            None
+        } else {
+            Some(ItemEventKind::Edited)
+        }
+    }
+}
+
+// 事件 ID，格式为 纪元-序号，如 1753400000000-42
+// 纪元为服务启动时的毫秒时间戳，序号在每次启动后从 1 开始递增
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct EventId {
+    pub epoch: u64,
+    pub seq: u64,
+}
+
+impl EventId {
+    // 解析事件 ID；没有纪元的旧格式 ID 视为纪元 0，总是与当前纪元不同
+    pub fn parse(id: &str) -> Option<Self> {
+        let id = id.trim();
+        match id.split_once('-') {
+            Some((epoch, seq)) => Some(EventId {
+                epoch: epoch.parse().ok()?,
+                seq: seq.parse().ok()?,
+            }),
+            None => Some(EventId {
+                epoch: 0,
+                seq: id.parse().ok()?,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for EventId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.epoch, self.seq)
+    }
+}
+
+impl Serialize for EventId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// 一条Hitokoto事件
+#[derive(Serialize, Clone)]
+pub struct ItemEvent {
+    pub id: EventId,
+    pub event: ItemEventKind,
+    pub uuid: String,
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub status: ItemStatus,
+    pub item: Option<HitokotoItem>, // 条目未公开时为空，避免泄露待审核的内容
+    pub created_at: u64,
+}
+
+impl ItemEvent {
+    // 转为 SSE 事件，事件名为事件类型，ID 用于断线后通过 Last-Event-ID 续传
+    pub fn to_sse(&self) -> Event {
+        Event::json(self)
+            .event(self.event.as_str())
+            .id(self.id.to_string())
+    }
+}
+
+// 事件流的查询参数
+#[derive(FromForm, Default)]
+pub struct EventQuery {
+    #[field(name = "type")]
+    pub item_type: Option<String>, // 只接收该分类的事件，支持分类名称和别名
+}
+
+// 请求头 Last-Event-ID，浏览器的 EventSource 断线重连时自动带上
+pub struct LastEventId(pub Option<EventId>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for LastEventId {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let id = request
+            .headers()
+            .get_one("Last-Event-ID")
+            .and_then(EventId::parse);
+        request::Outcome::Success(LastEventId(id))
+    }
+}
+
+// 一个事件流订阅：需要先发送的积压事件，以及之后的新事件
+pub struct EventSubscription {
+    pub backlog: Vec<ItemEvent>,
+    pub receiver: broadcast::Receiver<ItemEvent>,
+    pub item_type: Option<String>, // 规范的分类代码
+}
+
+impl EventSubscription {
+    pub fn matches(&self, event: &ItemEvent) -> bool {
+        self.item_type
+            .as_deref()
+            .is_none_or(|item_type| event.item_type == item_type)
+    }
+}
+
+// 事件日志：保存最近的事件用于续传，并广播给所有订阅者
+// 发布和订阅都在同一把锁下进行，订阅者不会漏掉或重复收到积压与广播之间的事件
+pub struct EventLog {
+    epoch: u64,
+    next_id: u64,
+    backlog: VecDeque<ItemEvent>,
+    capacity: usize,
+    sender: broadcast::Sender<ItemEvent>,
+}
+
+impl EventLog {
+    // capacity 为保存的最近事件数，以当前的毫秒时间戳作为纪元
+    pub fn new(capacity: usize) -> Self {
+        let epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let (sender, _) = broadcast::channel(capacity.max(16));
+        EventLog {
+            epoch,
+            next_id: 1,
+            backlog: VecDeque::with_capacity(capacity),
+            capacity,
+            sender,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.backlog.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.backlog.is_empty()
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    // 发布事件，item 为要附带的条目内容
+    pub fn publish(
+        &mut self,
+        kind: ItemEventKind,
+        subject: &HitokotoItem,
+        item: Option<HitokotoItem>,
+    ) -> ItemEvent {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let event = ItemEvent {
+            id: EventId {
+                epoch: self.epoch,
+                seq: self.next_id,
+            },
+            event: kind,
+            uuid: subject.uuid.clone(),
+            item_type: subject.item_type.clone(),
+            status: subject.status,
+            item,
+            created_at,
+        };
+        self.next_id += 1;
+        if self.capacity > 0 {
+            if self.backlog.len() == self.capacity {
+                self.backlog.pop_front();
+            }
+            self.backlog.push_back(event.clone());
+        }
+        // 没有订阅者时发送失败，忽略即可
+        let _ = self.sender.send(event.clone());
+        event
+    }
+
+    // 订阅新事件，并取出 last_event_id 之后仍在积压中的事件
+    // last_event_id 的纪元与当前不同时说明服务已重启，发送全部积压事件
+    pub fn subscribe(
+        &self,
+        last_event_id: Option<EventId>,
+        item_type: Option<String>,
+    ) -> EventSubscription {
+        let mut subscription = EventSubscription {
+            backlog: Vec::new(),
+            receiver: self.sender.subscribe(),
+            item_type,
+        };
+        if let Some(last_event_id) = last_event_id {
+            let after = if last_event_id.epoch == self.epoch {
+                last_event_id.seq
+            } else {
+                0
+            };
+            subscription.backlog = self
+                .backlog
+                .iter()
+                .filter(|event| event.id.seq > after && subscription.matches(event))
+                .cloned()
+                .collect();
+        }
+        subscription
+    }
+}
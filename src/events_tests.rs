@@ -0,0 +1,84 @@
+use crate::ItemStatus;
+use crate::events::{EventId, EventLog, ItemEvent, ItemEventKind};
+use crate::test_support::sample_item;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_log_backlog_and_resume() {
+        // 测试事件类型的判断、积压事件的上限，以及按 Last-Event-ID 和分类续传
+        let item = sample_item("一言");
+        let mut pending = item.clone();
+        pending.status = ItemStatus::Pending;
+        let mut edited = item.clone();
+        edited.hitokoto = "一言（修订）".to_string();
+        assert_eq!(
+            ItemEventKind::classify(None, &pending),
+            Some(ItemEventKind::Created)
+        );
+        assert_eq!(
+            ItemEventKind::classify(Some(&pending), &item),
+            Some(ItemEventKind::Approved)
+        );
+        assert_eq!(
+            ItemEventKind::classify(Some(&item), &pending),
+            Some(ItemEventKind::Deleted)
+        );
+        assert_eq!(
+            ItemEventKind::classify(Some(&item), &edited),
+            Some(ItemEventKind::Edited)
+        );
+        assert_eq!(ItemEventKind::classify(Some(&item), &item), None);
+
+        let mut other = item.clone();
+        other.item_type = "b".to_string();
+        let mut log = EventLog::new(3);
+        for i in 0..4 {
+            let subject = if i % 2 == 0 { &item } else { &other };
+            log.publish(ItemEventKind::Created, subject, None);
+        }
+        assert_eq!(log.len(), 3);
+
+        let epoch = log.epoch();
+        let id = |seq| Some(EventId { epoch, seq });
+        let ids = |backlog: Vec<ItemEvent>| -> Vec<u64> {
+            backlog.iter().map(|event| event.id.seq).collect()
+        };
+        assert!(log.subscribe(None, None).backlog.is_empty());
+        assert_eq!(ids(log.subscribe(id(2), None).backlog), [3, 4]);
+        assert_eq!(ids(log.subscribe(id(0), None).backlog), [2, 3, 4]);
+        assert_eq!(
+            ids(log.subscribe(id(1), Some("b".to_string())).backlog),
+            [2, 4]
+        );
+        // 服务重启后纪元不同，即使序号比现有的小也补发全部积压事件
+        let previous = EventId {
+            epoch: epoch - 1,
+            seq: 3,
+        };
+        assert_eq!(ids(log.subscribe(Some(previous), None).backlog), [2, 3, 4]);
+
+        let mut subscription = log.subscribe(None, None);
+        log.publish(ItemEventKind::Edited, &item, Some(item.clone()));
+        let event = subscription.receiver.try_recv().unwrap();
+        assert_eq!((event.id.seq, event.event), (5, ItemEventKind::Edited));
+        assert_eq!(event.id.to_string(), format!("{}-5", epoch));
+    }
+
+    #[test]
+    fn test_event_id_parse() {
+        // 测试事件 ID 的解析，没有纪元的旧格式视为纪元 0
+        assert_eq!(
+            EventId::parse("1753400000000-42"),
+            Some(EventId {
+                epoch: 1_753_400_000_000,
+                seq: 42
+            })
+        );
+        assert_eq!(EventId::parse(" 42 "), Some(EventId { epoch: 0, seq: 42 }));
+        assert_eq!(EventId::parse("abc-1"), None);
+        assert_eq!(EventId::parse(""), None);
+    }
+}
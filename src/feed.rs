@@ -0,0 +1,291 @@
+use crate::daily::{civil_from_days, days_from_civil, stable_hash};
+use crate::item::HitokotoItem;
+use crate::render::{escape_xml, from_line};
+use rocket::http::{ContentType, Header, Status};
+use rocket::request::{self, FromRequest, Request};
+use rocket::response::{self, Responder, Response};
+use std::io::Cursor;
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// 订阅源的范围
+pub enum FeedScope {
+    All,                // 全站最新条目
+    User(u32),          // 某个用户提交的条目
+    Collection(String), // 某个文集中的条目
+}
+
+impl FeedScope {
+    // 订阅源在站内的路径，不含扩展名
+    pub fn path(&self) -> String {
+        match self {
+            FeedScope::All => "/feed".to_string(),
+            FeedScope::User(user_id) => format!("/user/{}/feed", user_id),
+            FeedScope::Collection(collection_id) => format!("/collection/{}/feed", collection_id),
+        }
+    }
+}
+
+// 订阅源格式
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+}
+
+impl FeedFormat {
+    pub fn content_type(self) -> ContentType {
+        match self {
+            FeedFormat::Rss => ContentType::new("application", "rss+xml"),
+            FeedFormat::Atom => ContentType::new("application", "atom+xml"),
+        }
+    }
+}
+
+// 订阅源的内容，条目按创建时间从新到旧排列
+pub struct Feed {
+    pub title: String,
+    pub link: String,     // 对应页面的完整网址
+    pub feed_url: String, // 订阅源自身的完整网址，不含扩展名
+    pub updated: u64,     // 订阅源内容的最后修改时间，用作 Last-Modified
+    pub items: Vec<HitokotoItem>,
+}
+
+impl Feed {
+    pub fn render(&self, format: FeedFormat) -> String {
+        match format {
+            FeedFormat::Rss => self.to_rss(),
+            FeedFormat::Atom => self.to_atom(),
+        }
+    }
+
+    // RSS 2.0，作者使用 dc:creator，因为 RSS 的 author 要求是电子邮件地址
+    fn to_rss(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n");
+        xml.push_str("  <channel>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", escape_xml(&self.title)));
+        xml.push_str(&format!("    <link>{}</link>\n", escape_xml(&self.link)));
+        xml.push_str(&format!(
+            "    <description>{}</description>\n",
+            escape_xml(&self.title)
+        ));
+        xml.push_str(&format!(
+            "    <atom:link href=\"{}.rss\" rel=\"self\" type=\"application/rss+xml\"/>\n",
+            escape_xml(&self.feed_url)
+        ));
+        xml.push_str(&format!(
+            "    <lastBuildDate>{}</lastBuildDate>\n",
+            http_date(self.updated)
+        ));
+        for item in &self.items {
+            xml.push_str("    <item>\n");
+            xml.push_str(&format!(
+                "      <title>{}</title>\n",
+                escape_xml(&item.hitokoto)
+            ));
+            xml.push_str(&format!(
+                "      <description>{} {}</description>\n",
+                escape_xml(&item.hitokoto),
+                escape_xml(&from_line(item))
+            ));
+            xml.push_str(&format!(
+                "      <dc:creator>{}</dc:creator>\n",
+                escape_xml(&item.user)
+            ));
+            xml.push_str(&format!(
+                "      <guid isPermaLink=\"false\">{}</guid>\n",
+                escape_xml(&item.uuid)
+            ));
+            xml.push_str(&format!(
+                "      <pubDate>{}</pubDate>\n",
+                http_date(item.created_at)
+            ));
+            for tag in &item.tags {
+                xml.push_str(&format!("      <category>{}</category>\n", escape_xml(tag)));
+            }
+            xml.push_str("    </item>\n");
+        }
+        xml.push_str("  </channel>\n</rss>\n");
+        xml
+    }
+
+    // Atom 1.0，条目 ID 使用 urn:uuid
+    fn to_atom(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        xml.push_str(&format!("  <title>{}</title>\n", escape_xml(&self.title)));
+        xml.push_str(&format!("  <id>{}.atom</id>\n", escape_xml(&self.feed_url)));
+        xml.push_str(&format!(
+            "  <link href=\"{}\" rel=\"alternate\"/>\n",
+            escape_xml(&self.link)
+        ));
+        xml.push_str(&format!(
+            "  <link href=\"{}.atom\" rel=\"self\" type=\"application/atom+xml\"/>\n",
+            escape_xml(&self.feed_url)
+        ));
+        xml.push_str(&format!("  <updated>{}</updated>\n", rfc3339(self.updated)));
+        for item in &self.items {
+            xml.push_str("  <entry>\n");
+            xml.push_str(&format!(
+                "    <title>{}</title>\n",
+                escape_xml(&item.hitokoto)
+            ));
+            xml.push_str(&format!(
+                "    <id>urn:uuid:{}</id>\n",
+                escape_xml(&item.uuid)
+            ));
+            xml.push_str(&format!(
+                "    <published>{}</published>\n",
+                rfc3339(item.created_at)
+            ));
+            xml.push_str(&format!(
+                "    <updated>{}</updated>\n",
+                rfc3339(item.modified_at())
+            ));
+            xml.push_str(&format!(
+                "    <author><name>{}</name></author>\n",
+                escape_xml(&item.user)
+            ));
+            xml.push_str(&format!(
+                "    <content type=\"text\">{} {}</content>\n",
+                escape_xml(&item.hitokoto),
+                escape_xml(&from_line(item))
+            ));
+            for tag in &item.tags {
+                xml.push_str(&format!("    <category term=\"{}\"/>\n", escape_xml(tag)));
+            }
+            xml.push_str("  </entry>\n");
+        }
+        xml.push_str("</feed>\n");
+        xml
+    }
+}
+
+// 时间戳转为 HTTP 和 RSS 使用的日期，如 Sun, 06 Nov 1994 08:49:37 GMT
+pub fn http_date(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let seconds = timestamp % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[(days + 4).rem_euclid(7) as usize],
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+// 解析 HTTP 日期，只支持 http_date 生成的格式，无效时返回 None
+pub fn parse_http_date(date: &str) -> Option<u64> {
+    let parts: Vec<&str> = date.split_whitespace().collect();
+    let [_, day, month, year, time, "GMT"] = parts.as_slice() else {
+        return None;
+    };
+    let day: u32 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|name| name == month)? as u32 + 1;
+    let year: i64 = year.parse().ok()?;
+    let mut clock = time.splitn(3, ':').map(|part| part.parse::<u64>().ok());
+    let (hour, minute, second) = (clock.next()??, clock.next()??, clock.next()??);
+    if day == 0 || day > 31 || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+// 时间戳转为 Atom 使用的 RFC 3339 日期，如 1994-11-06T08:49:37Z
+pub fn rfc3339(timestamp: u64) -> String {
+    let (year, month, day) = civil_from_days((timestamp / 86_400) as i64);
+    let seconds = timestamp % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+// 条件请求的请求头，用于判断订阅源自上次获取后是否有变化
+pub struct ConditionalHeaders {
+    pub if_none_match: Option<String>,
+    pub if_modified_since: Option<u64>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ConditionalHeaders {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let headers = request.headers();
+        request::Outcome::Success(ConditionalHeaders {
+            if_none_match: headers.get_one("If-None-Match").map(str::to_string),
+            if_modified_since: headers
+                .get_one("If-Modified-Since")
+                .and_then(parse_http_date),
+        })
+    }
+}
+
+impl ConditionalHeaders {
+    // 客户端的缓存是否仍然有效，提供 If-None-Match 时忽略 If-Modified-Since
+    pub fn is_fresh(&self, etag: &str, last_modified: u64) -> bool {
+        match &self.if_none_match {
+            Some(if_none_match) => if_none_match.split(',').any(|tag| {
+                let tag = tag.trim();
+                tag == "*" || tag.trim_start_matches("W/") == etag
+            }),
+            None => self
+                .if_modified_since
+                .is_some_and(|since| last_modified <= since),
+        }
+    }
+}
+
+// 订阅源的应答，客户端缓存有效时返回 304 且不带正文
+pub struct FeedResponse {
+    pub format: FeedFormat,
+    pub body: Option<String>,
+    pub etag: String,
+    pub last_modified: u64,
+}
+
+impl FeedResponse {
+    pub fn new(feed: &Feed, format: FeedFormat, conditional: &ConditionalHeaders) -> Self {
+        let body = feed.render(format);
+        let etag = format!("\"{:016x}\"", stable_hash(body.bytes()));
+        let fresh = conditional.is_fresh(&etag, feed.updated);
+        FeedResponse {
+            format,
+            body: (!fresh).then_some(body),
+            etag,
+            last_modified: feed.updated,
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for FeedResponse {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let mut response = Response::build();
+        response
+            .header(Header::new("ETag", self.etag))
+            .header(Header::new("Last-Modified", http_date(self.last_modified)))
+            .header(Header::new("Cache-Control", "no-cache"));
+        match self.body {
+            Some(body) => response
+                .header(self.format.content_type())
+                .sized_body(body.len(), Cursor::new(body)),
+            None => response.status(Status::NotModified),
+        };
+        response.ok()
+    }
+}
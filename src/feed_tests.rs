@@ -0,0 +1,118 @@
+use crate::feed::{
+    ConditionalHeaders, Feed, FeedFormat, FeedResponse, FeedScope, http_date, parse_http_date,
+    rfc3339,
+};
+use crate::item::ItemStatus;
+use crate::storage::{AppState, get_feed};
+use crate::test_support::{sample_item, sample_items};
+use rocket::State;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_dates() {
+        // 测试 HTTP 日期和 RFC 3339 日期的格式，以及 HTTP 日期的解析
+        assert_eq!(http_date(784_111_777), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(rfc3339(784_111_777), "1994-11-06T08:49:37Z");
+        assert_eq!(http_date(0), "Thu, 01 Jan 1970 00:00:00 GMT");
+        assert_eq!(
+            parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(784_111_777)
+        );
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 25:00:00 GMT"), None);
+    }
+
+    #[test]
+    fn test_feed_conditional_requests() {
+        // 测试 ETag 与 Last-Modified 的条件请求，以及条目内容的转义
+        let mut item = sample_item("<一言> & 订阅");
+        item.created_at = 1_000;
+        let mut feed = Feed {
+            title: "Hitokoto 最新条目".to_string(),
+            link: "http://localhost:8000/hitokoto".to_string(),
+            feed_url: "http://localhost:8000/feed".to_string(),
+            updated: item.created_at,
+            items: vec![item],
+        };
+        let unconditional = ConditionalHeaders {
+            if_none_match: None,
+            if_modified_since: None,
+        };
+        let first = FeedResponse::new(&feed, FeedFormat::Atom, &unconditional);
+        let body = first.body.unwrap();
+        assert!(body.contains("<title>&lt;一言&gt; &amp; 订阅</title>"));
+        assert!(body.contains("<author><name>tester</name></author>"));
+
+        let etag_match = ConditionalHeaders {
+            if_none_match: Some(format!("\"other\", W/{}", first.etag)),
+            if_modified_since: None,
+        };
+        assert!(
+            FeedResponse::new(&feed, FeedFormat::Atom, &etag_match)
+                .body
+                .is_none()
+        );
+        // 不同格式的正文不同，ETag 也不同
+        assert!(
+            FeedResponse::new(&feed, FeedFormat::Rss, &etag_match)
+                .body
+                .is_some()
+        );
+
+        let since = |timestamp| ConditionalHeaders {
+            if_none_match: None,
+            if_modified_since: Some(timestamp),
+        };
+        assert!(
+            FeedResponse::new(&feed, FeedFormat::Rss, &since(1_000))
+                .body
+                .is_none()
+        );
+        assert!(
+            FeedResponse::new(&feed, FeedFormat::Rss, &since(999))
+                .body
+                .is_some()
+        );
+        // 同时提供两者时只比较 If-None-Match
+        let both = ConditionalHeaders {
+            if_none_match: Some("\"other\"".to_string()),
+            if_modified_since: Some(1_000),
+        };
+        assert!(
+            FeedResponse::new(&feed, FeedFormat::Rss, &both)
+                .body
+                .is_some()
+        );
+
+        // 条目被编辑后正文和 ETag 随之变化
+        feed.items[0].hitokoto = "编辑后的一言".to_string();
+        assert!(
+            FeedResponse::new(&feed, FeedFormat::Atom, &etag_match)
+                .body
+                .is_some()
+        );
+    }
+
+    #[rocket::async_test]
+    async fn test_feed_updated_tracks_modifications() {
+        // 测试订阅源的最后修改时间随条目的修改变化，包括离开订阅源的条目
+        let state = AppState::with_config(Default::default());
+        let mut items = sample_items(3);
+        for (i, item) in items.iter_mut().enumerate() {
+            item.created_at = 1_000 + i as u64;
+            item.updated_at = item.created_at;
+        }
+        items[0].updated_at = 5_000;
+        items[0].status = ItemStatus::Rejected;
+        state.data.lock().await.extend(items);
+
+        let feed = get_feed(State::from(&state), &FeedScope::All)
+            .await
+            .unwrap();
+        assert_eq!(feed.items.len(), 2);
+        assert_eq!(feed.updated, 5_000);
+    }
+}
@@ -1,4 +1,10 @@
+use crate::collection::CollectionQuery;
+use crate::config::ValidationConfig;
+use crate::error::{AppError, FieldError};
+use crate::strategy::SelectionStrategy;
+use rocket::{FromForm, FromFormField};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
@@ -14,7 +20,50 @@ pub struct HitokotoItem {
     pub user: String,
     pub user_id: u32,
     pub created_at: u64,
+    #[serde(default)]
+    pub updated_at: u64, // 内容、标签或审核状态最后一次变化的时间，旧数据为 0
     pub length: u32,
+    #[serde(default)]
+    pub tags: BTreeSet<String>, // 规范化后的标签
+    #[serde(default)]
+    pub status: ItemStatus,
+    #[serde(default)]
+    pub similar_to: Vec<String>, // 提交时检测到的近似重复条目，供审核参考
+    #[serde(default)]
+    pub work_id: Option<String>, // 根据 from 匹配到的作品
+    #[serde(default)]
+    pub author_id: Option<String>, // 根据 from_who 匹配到的作者
+    #[serde(default)]
+    pub like_count: u32,
+}
+
+// Hitokoto审核状态，只有已通过的条目会公开展示
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemStatus {
+    #[default]
+    Approved,
+    Pending,
+    Rejected,
+}
+
+// 点赞或取消点赞的请求，user_id 为操作者
+#[derive(Deserialize)]
+pub struct LikeRequest {
+    pub user_id: u32,
+}
+
+// 点赞操作的结果
+#[derive(Serialize)]
+pub struct LikeResponse {
+    pub liked: bool,
+    pub like_count: u32,
+}
+
+// 审核操作的请求，user_id 为操作者
+#[derive(Deserialize)]
+pub struct ModerationRequest {
+    pub user_id: u32,
 }
 
 // 提交的Hitokoto条目（无UUID、uid、时间戳、长度)
@@ -26,6 +75,228 @@ pub struct RequestedHitokotoItem {
     pub from: String,
     pub from_who: Option<String>,
     pub user_id: u32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+// 获取随机Hitokoto时的查询参数
+#[derive(FromForm, Default)]
+pub struct RandomItemQuery {
+    pub collection: Option<String>, // 仅从指定文集中选取
+    pub tag: Vec<String>,           // 仅选取同时带有这些标签的条目
+    // 随机选取策略，默认使用配置中的 default_strategy；旧的参数名 sort 仍然有效
+    #[field(name = "strategy")]
+    #[field(name = "sort")]
+    pub strategy: Option<SelectionStrategy>,
+    pub client: Option<String>, // 客户端令牌，提供时按该客户端的随机排列不重复地选取
+    pub seed: Option<u64>,      // 随机种子，相同种子和候选条目总是得到相同的结果
+    pub count: Option<usize>,   // 一次获取的条目数，提供时以数组返回
+}
+
+// 随机获取的结果，附带本次选取使用的种子以便复现
+#[derive(Serialize)]
+pub struct RandomItemResponse {
+    #[serde(flatten)]
+    pub item: HitokotoItem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>, // 按客户端排列选取时为空
+}
+
+// 随机获取的结果，未指定 count 时为单个条目，否则为条目数组
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum RandomItemResult {
+    One(Box<RandomItemResponse>),
+    Many(Vec<RandomItemResponse>),
+}
+
+// 客户端令牌的最大长度
+pub const MAX_CLIENT_TOKEN_LENGTH: usize = 64;
+
+impl RequestedHitokotoItem {
+    // 按配置的规则校验提交内容，返回全部字段错误
+    pub fn validate(&self, rules: &ValidationConfig) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        let length = self.hitokoto.trim().chars().count();
+        if length < rules.min_length.max(1) {
+            errors.push(FieldError::new("hitokoto", "内容不能为空或过短"));
+        } else if length > rules.max_length {
+            errors.push(FieldError::new(
+                "hitokoto",
+                format!("内容不能超过{}个字符", rules.max_length),
+            ));
+        }
+        if rules.require_from && self.from.trim().is_empty() {
+            errors.push(FieldError::new("from", "来源不能为空"));
+        }
+        if self.from.chars().count() > rules.max_from_length {
+            errors.push(FieldError::new(
+                "from",
+                format!("来源不能超过{}个字符", rules.max_from_length),
+            ));
+        }
+        if let Some(from_who) = &self.from_who
+            && from_who.chars().count() > rules.max_from_who_length
+        {
+            errors.push(FieldError::new(
+                "from_who",
+                format!("作者不能超过{}个字符", rules.max_from_who_length),
+            ));
+        }
+
+        // 逐字段检查字符和网址
+        let fields = [
+            ("hitokoto", Some(&self.hitokoto)),
+            ("from", Some(&self.from)),
+            ("from_who", self.from_who.as_ref()),
+        ];
+        for (field, value) in fields {
+            let Some(value) = value else { continue };
+            if value.chars().any(char::is_control) {
+                errors.push(FieldError::new(field, "不能包含控制字符"));
+            }
+            let disallowed: Vec<char> = value
+                .chars()
+                .filter(|c| rules.disallowed_chars.contains(*c))
+                .collect();
+            if !disallowed.is_empty() {
+                let chars: String = disallowed.into_iter().collect();
+                errors.push(FieldError::new(
+                    field,
+                    format!("包含不允许的字符: {}", chars),
+                ));
+            }
+            if !rules.allow_urls && contains_url(value) {
+                errors.push(FieldError::new(field, "不能包含网址"));
+            }
+        }
+
+        errors
+    }
+}
+
+// 粗略判断文本中是否包含网址
+fn contains_url(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    lower.contains("://") || lower.contains("www.")
+}
+
+// 列表排序字段
+#[derive(FromFormField, Clone, Copy, Default, PartialEq)]
+pub enum ItemSort {
+    #[default]
+    #[field(value = "created_at")]
+    CreatedAt,
+    #[field(value = "length")]
+    Length,
+}
+
+impl ItemSort {
+    // 排序字段在查询参数和游标中的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemSort::CreatedAt => "created_at",
+            ItemSort::Length => "length",
+        }
+    }
+}
+
+// 列表排序方向
+#[derive(FromFormField, Clone, Copy, Default, PartialEq)]
+pub enum SortOrder {
+    #[field(value = "asc")]
+    Asc,
+    #[default]
+    #[field(value = "desc")]
+    Desc,
+}
+
+impl SortOrder {
+    // 排序方向在查询参数和游标中的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+// 分页浏览Hitokoto的查询参数
+#[derive(FromForm, Default)]
+pub struct ListItemsQuery {
+    pub cursor: Option<String>, // 上一页返回的 next_cursor
+    pub limit: Option<usize>,   // 默认 20，最大 100
+    pub sort: Option<ItemSort>,
+    pub order: Option<SortOrder>,
+    #[field(name = "type")]
+    pub item_type: Option<String>,
+    pub user_id: Option<u32>,
+    pub from: Option<String>,
+    pub from_who: Option<String>,
+    pub created_after: Option<u64>,
+    pub created_before: Option<u64>,
+    pub tag: Vec<String>,
+}
+
+// 分页浏览的结果，next_cursor 为空表示没有更多数据
+#[derive(Serialize)]
+pub struct ItemPage {
+    pub items: Vec<HitokotoItem>,
+    pub next_cursor: Option<String>,
+}
+
+impl ListItemsQuery {
+    // 转换为通用的过滤条件
+    pub fn filter(&self) -> CollectionQuery {
+        CollectionQuery {
+            item_type: self.item_type.clone(),
+            from: self.from.clone(),
+            from_who: self.from_who.clone(),
+            user_id: self.user_id,
+            created_after: self.created_after,
+            created_before: self.created_before,
+            tags: self.tag.clone(),
+            ..Default::default()
+        }
+    }
+
+    // 条目在当前排序下的排序键，相同时以 UUID 区分保证顺序稳定
+    pub fn sort_key(&self, item: &HitokotoItem) -> (u64, String) {
+        let key = match self.sort.unwrap_or_default() {
+            ItemSort::CreatedAt => item.created_at,
+            ItemSort::Length => item.length as u64,
+        };
+        (key, item.uuid.clone())
+    }
+}
+
+// 将排序键编码为游标，格式为 “排序字段:排序方向:键:UUID”
+pub fn encode_cursor(sort: ItemSort, order: SortOrder, key: &(u64, String)) -> String {
+    format!("{}:{}:{}:{}", sort.as_str(), order.as_str(), key.0, key.1)
+}
+
+// 解析游标，游标须由相同的排序字段和方向生成，否则不同含义的键会被混在一起比较
+pub fn decode_cursor(
+    cursor: &str,
+    sort: ItemSort,
+    order: SortOrder,
+) -> Result<(u64, String), AppError> {
+    let invalid = || AppError::Validation(format!("无效的游标: {}", cursor));
+    let mut parts = cursor.splitn(4, ':');
+    let (Some(cursor_sort), Some(cursor_order), Some(key), Some(uuid)) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid());
+    };
+    let key: u64 = key.parse().map_err(|_| invalid())?;
+    if cursor_sort != sort.as_str() || cursor_order != order.as_str() {
+        return Err(AppError::Validation(format!(
+            "游标由 sort={}&order={} 生成，与当前的排序方式不一致",
+            cursor_sort, cursor_order
+        )));
+    }
+    Ok((key, uuid.to_string()))
 }
 
 impl HitokotoItem {
@@ -53,7 +324,32 @@ impl HitokotoItem {
             user,
             user_id,
             created_at,
+            updated_at: created_at,
             length,
+            tags: BTreeSet::new(),
+            status: ItemStatus::Approved,
+            similar_to: Vec::new(),
+            work_id: None,
+            author_id: None,
+            like_count: 0,
         }
     }
+
+    // 是否公开展示（随机获取、列表和搜索只返回已通过的条目）
+    pub fn is_public(&self) -> bool {
+        self.status == ItemStatus::Approved
+    }
+
+    // 最后修改时间，旧数据没有记录修改时间时取创建时间
+    pub fn modified_at(&self) -> u64 {
+        self.updated_at.max(self.created_at)
+    }
+
+    // 记录条目在此刻被修改
+    pub fn touch(&mut self) {
+        self.updated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+    }
 }
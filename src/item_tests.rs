@@ -0,0 +1,71 @@
+use crate::item::{ItemSort, ListItemsQuery, SortOrder, decode_cursor, encode_cursor};
+use crate::test_support::sample_item;
+use crate::{AppError, RequestedHitokotoItem, ValidationConfig};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_cursor_round_trip() {
+        // 测试游标编码与解析，排序方式不一致的游标被拒绝
+        let item = sample_item("一言");
+        let query = ListItemsQuery {
+            sort: Some(ItemSort::Length),
+            ..Default::default()
+        };
+        let key = query.sort_key(&item);
+        assert_eq!(key.0, 2);
+        let cursor = encode_cursor(ItemSort::Length, SortOrder::Asc, &key);
+        assert_eq!(
+            decode_cursor(&cursor, ItemSort::Length, SortOrder::Asc).unwrap(),
+            key
+        );
+
+        for (sort, order) in [
+            (ItemSort::CreatedAt, SortOrder::Asc),
+            (ItemSort::Length, SortOrder::Desc),
+        ] {
+            assert!(matches!(
+                decode_cursor(&cursor, sort, order),
+                Err(AppError::Validation(_))
+            ));
+        }
+        assert!(matches!(
+            decode_cursor("not-a-cursor", ItemSort::Length, SortOrder::Asc),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_submission_validation_reports_all_fields() {
+        // 测试提交校验一次返回所有字段的错误
+        let rules = ValidationConfig::default();
+        let request = RequestedHitokotoItem {
+            hitokoto: "访问 https://example.com\u{7}".to_string(),
+            item_type: "a".to_string(),
+            from: " ".to_string(),
+            from_who: Some("<作者>".to_string()),
+            user_id: 1,
+            tags: Vec::new(),
+        };
+
+        let errors = request.validate(&rules);
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["from", "hitokoto", "hitokoto", "from_who"]);
+
+        let valid = RequestedHitokotoItem {
+            hitokoto: "岁月不居，时节如流。".to_string(),
+            from: "与曹公论盛孝章书".to_string(),
+            from_who: Some("孔融".to_string()),
+            ..request
+        };
+        assert!(valid.validate(&rules).is_empty());
+
+        let strict = ValidationConfig {
+            max_length: 5,
+            ..ValidationConfig::default()
+        };
+        assert_eq!(valid.validate(&strict).len(), 1);
+    }
+}
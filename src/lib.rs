@@ -1,19 +1,126 @@
 // 模块声明
+pub mod category;
 pub mod collection;
+pub mod config;
+pub mod daily;
+pub mod dedup;
 pub mod error;
+pub mod events;
+pub mod feed;
 pub mod item;
+pub mod render;
+pub mod report;
+pub mod revision;
+pub mod search;
+pub mod sensitive;
+pub mod shuffle;
+pub mod source;
 pub mod storage;
+pub mod strategy;
+pub mod tag;
 pub mod user;
+pub mod webhook;
 
+#[cfg(test)]
+mod category_tests;
+#[cfg(test)]
+mod collection_tests;
+#[cfg(test)]
+mod daily_tests;
+#[cfg(test)]
+mod dedup_tests;
 #[cfg(test)]
 mod error_tests;
+#[cfg(test)]
+mod events_tests;
+#[cfg(test)]
+mod feed_tests;
+#[cfg(test)]
+mod item_tests;
+#[cfg(test)]
+mod render_tests;
+#[cfg(test)]
+mod report_tests;
+#[cfg(test)]
+mod revision_tests;
+#[cfg(test)]
+mod search_tests;
+#[cfg(test)]
+mod sensitive_tests;
+#[cfg(test)]
+mod shuffle_tests;
+#[cfg(test)]
+mod source_tests;
+#[cfg(test)]
+mod storage_tests;
+#[cfg(test)]
+mod strategy_tests;
+#[cfg(test)]
+mod tag_tests;
+#[cfg(test)]
+mod test_support;
+#[cfg(test)]
+mod webhook_tests;
 
 // 重新导出主要类型和函数
-pub use collection::{AddToCollectionRequest, Collection, NewCollectionRequest};
-pub use error::{AppError, AppResult, ErrorResponse};
-pub use item::{HitokotoItem, RequestedHitokotoItem};
+pub use category::{Category, CategoryRegistry, CategoryWithCount};
+pub use collection::{
+    AddToCollectionResult, AnnotateEntryRequest, Collection, CollectionEntriesRequest,
+    CollectionEntry, CollectionQuery, DeleteMode, ForkCollectionRequest, MoveCollectionRequest,
+    NewCollectionRequest,
+};
+pub use config::{AppConfig, SvgConfig, ValidationConfig, WebhookConfig};
+pub use daily::{
+    DailyArchive, DailyArchiveEntry, DailyArchiveQuery, DailyPick, DailyPinRequest, DailyResponse,
+};
+pub use error::{AppError, AppResult, ErrorResponse, FieldError};
+pub use events::{
+    EventId, EventLog, EventQuery, EventSubscription, ItemEvent, ItemEventKind, LastEventId,
+};
+pub use feed::{
+    ConditionalHeaders, Feed, FeedFormat, FeedResponse, FeedScope, http_date, parse_http_date,
+    rfc3339,
+};
+pub use item::{
+    HitokotoItem, ItemPage, ItemSort, ItemStatus, LikeRequest, LikeResponse, ListItemsQuery,
+    ModerationRequest, RandomItemQuery, RandomItemResponse, RandomItemResult,
+    RequestedHitokotoItem, SortOrder,
+};
+pub use render::{OutputFormat, RenderOptions, render_js, render_svg, wrap_text};
+pub use report::{
+    HandleReportRequest, Report, ReportInboxQuery, ReportReason, ReportRequest, ReportStatus,
+    ReportTarget,
+};
+pub use revision::{
+    EditHitokotoRequest, FieldChange, RevertRequest, Revision, RevisionAction, diff_items,
+    item_at_revision,
+};
+pub use search::{SearchHit, SearchIndex, SearchQuery, SearchResponse};
+pub use sensitive::{
+    SensitiveAction, SensitiveFilter, SensitiveMatch, SensitiveReloadRequest, SensitiveTestRequest,
+    SensitiveTestResponse, SensitiveWord,
+};
+pub use shuffle::ClientShuffles;
+pub use source::{
+    Author, MergeSourceRequest, NewSourceRequest, SourceEntity, SourceKind, SourceRegistry,
+    SourceWithItems, Work, normalize_name,
+};
 pub use storage::{
-    AppState, add_hitokoto_to_collection, add_item, add_user, create_collection, get_random_item,
-    get_user_by_id, get_user_with_details, load_data, save_item,
+    AppState, add_hitokoto_to_collection, add_item, add_user, annotate_collection_entry,
+    create_collection, create_source, delete_collection, delete_webhook, edit_item,
+    fork_collection, get_categories_with_counts, get_daily_archive, get_daily_item, get_feed,
+    get_item_history, get_pending_items, get_random_item, get_random_items, get_reports,
+    get_source_with_items, get_tag_counts, get_user_by_id, get_user_likes, get_user_with_details,
+    get_webhook_deliveries, handle_report, list_items, list_webhooks, load_data, merge_sources,
+    move_collection, pin_daily_item, register_webhook, reload_sensitive_words,
+    remove_hitokoto_from_collection, report_target, revert_item, save_item, search_items,
+    set_item_like, set_item_status, snapshot_collection, subscribe_events, test_sensitive_text,
+    update_item_tags,
+};
+pub use strategy::{SelectionContext, SelectionStrategy};
+pub use tag::{TagCount, TagIndex, TagListQuery, TagRequest};
+pub use user::{CollectionWithDetails, NewUserRequest, User, UserRole, UserWithDetails};
+pub use webhook::{
+    Delivery, DeliveryLogQuery, DeliveryStatus, NewWebhookRequest, Webhook, WebhookDispatcher,
+    WebhookEvent, sign,
 };
-pub use user::{CollectionWithDetails, NewUserRequest, User, UserWithDetails};
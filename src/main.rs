@@ -2,13 +2,38 @@
 extern crate rocket;
 
 use pencil_api::{
-    AddToCollectionRequest, AppState, Collection, ErrorResponse, 
-    HitokotoItem, NewCollectionRequest, NewUserRequest, RequestedHitokotoItem, User, 
-    UserWithDetails, add_hitokoto_to_collection, add_item, add_user, create_collection, 
-    get_random_item, get_user_with_details, load_data, save_item,
+    AnnotateEntryRequest, AppConfig, AppState, CategoryWithCount, Collection,
+    CollectionEntriesRequest, CollectionEntry, ConditionalHeaders, DailyArchiveEntry,
+    DailyArchiveQuery, DailyPinRequest, DailyResponse, DeleteMode, Delivery, DeliveryLogQuery,
+    EditHitokotoRequest, ErrorResponse, EventQuery, FeedFormat, FeedResponse, FeedScope,
+    ForkCollectionRequest, HandleReportRequest, HitokotoItem, ItemPage, ItemStatus, LastEventId,
+    LikeRequest, LikeResponse, ListItemsQuery, MergeSourceRequest, ModerationRequest,
+    MoveCollectionRequest, NewCollectionRequest, NewSourceRequest, NewUserRequest,
+    NewWebhookRequest, OutputFormat, RandomItemQuery, RandomItemResult, RenderOptions, Report,
+    ReportInboxQuery, ReportRequest, ReportTarget, RequestedHitokotoItem, RevertRequest, Revision,
+    SearchQuery, SearchResponse, SensitiveReloadRequest, SensitiveTestRequest,
+    SensitiveTestResponse, SourceEntity, SourceKind, SourceWithItems, TagCount, TagListQuery,
+    TagRequest, User, UserWithDetails, Webhook, add_hitokoto_to_collection, add_item, add_user,
+    annotate_collection_entry, create_collection, create_source, delete_collection, delete_webhook,
+    edit_item, fork_collection, get_categories_with_counts, get_daily_archive, get_daily_item,
+    get_feed, get_item_history, get_pending_items, get_random_item, get_random_items, get_reports,
+    get_source_with_items, get_tag_counts, get_user_likes, get_user_with_details,
+    get_webhook_deliveries, handle_report, list_items, list_webhooks, load_data, merge_sources,
+    move_collection, pin_daily_item, register_webhook, reload_sensitive_words,
+    remove_hitokoto_from_collection, report_target, revert_item, save_item, search_items,
+    set_item_like, set_item_status, snapshot_collection, subscribe_events, test_sensitive_text,
+    update_item_tags,
 };
+use rocket::fairing::AdHoc;
+use rocket::response::stream::EventStream;
 use rocket::serde::{Serialize, json::Json};
-use rocket::{State, http::Status, response::status};
+use rocket::tokio::select;
+use rocket::tokio::sync::broadcast::error::RecvError;
+use rocket::{
+    Responder, Shutdown, State,
+    http::{Accept, ContentType, CookieJar, Status},
+    response::status,
+};
 
 // 成功Hitokoto应答
 #[derive(Serialize)]
@@ -23,19 +48,159 @@ struct UserSuccessResponse {
     message: String,
     item: User,
 }
-#[get("/get")]
+// 未在查询参数中提供客户端令牌时读取的 Cookie 名称
+const CLIENT_COOKIE: &str = "hitokoto_client";
+
+// 随机获取的应答，JSON 或按 encode 渲染的文本、JavaScript、SVG
+#[derive(Responder)]
+enum RandomOutput {
+    Json(Json<RandomItemResult>),
+    Rendered((ContentType, String)),
+}
+
+#[get("/get?<encode>&<select>&<with_from>&<query..>")]
 async fn get_item(
+    encode: Option<OutputFormat>,
+    select: Option<String>,
+    with_from: Option<bool>,
+    mut query: RandomItemQuery,
+    cookies: &CookieJar<'_>,
+    accept: Option<&Accept>,
     state: &State<AppState>,
-) -> Result<Json<HitokotoItem>, status::Custom<Json<ErrorResponse>>> {
-    match get_random_item(state).await {
-        Some(item) => Ok(Json(item)),
-        None => {
+) -> Result<RandomOutput, status::Custom<Json<ErrorResponse>>> {
+    // 显式提供的种子优先于 Cookie 中的客户端令牌
+    if query.client.is_none() && query.seed.is_none() {
+        query.client = cookies
+            .get(CLIENT_COOKIE)
+            .map(|cookie| cookie.value().to_string());
+    }
+    // encode 参数优先于 Accept 头
+    let options = RenderOptions {
+        format: encode
+            .or_else(|| accept.map(OutputFormat::from_accept))
+            .unwrap_or_default(),
+        select,
+        with_from,
+    };
+    if let Err(e) = options.validate(query.count) {
+        let error_response = e.to_response();
+        return Err(status::Custom(e.status_code(), Json(error_response)));
+    }
+    let result = match query.count {
+        Some(count) => get_random_items(state, &query, count)
+            .await
+            .map(|items| (!items.is_empty()).then_some(RandomItemResult::Many(items))),
+        None => get_random_item(state, &query)
+            .await
+            .map(|item| item.map(|item| RandomItemResult::One(Box::new(item)))),
+    };
+    match result {
+        Ok(Some(result)) => match options.format {
+            OutputFormat::Json => Ok(RandomOutput::Json(Json(result))),
+            _ => Ok(RandomOutput::Rendered(
+                options.render(&result, &state.config.svg),
+            )),
+        },
+        Ok(None) => {
             let error_response = ErrorResponse {
                 error: "无法获取数据".to_string(),
                 code: "NO_DATA".to_string(),
+                fields: None,
             };
             Err(status::Custom(Status::NotFound, Json(error_response)))
         }
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[get("/events?<query..>")]
+async fn events(
+    query: EventQuery,
+    last_event_id: LastEventId,
+    state: &State<AppState>,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], status::Custom<Json<ErrorResponse>>> {
+    let mut subscription = match subscribe_events(state, &query, last_event_id.0).await {
+        Ok(subscription) => subscription,
+        Err(e) => {
+            let error_response = e.to_response();
+            return Err(status::Custom(e.status_code(), Json(error_response)));
+        }
+    };
+    Ok(EventStream! {
+        for event in std::mem::take(&mut subscription.backlog) {
+            yield event.to_sse();
+        }
+        loop {
+            let event = select! {
+                event = subscription.receiver.recv() => match event {
+                    Ok(event) => event,
+                    Err(RecvError::Closed) => break,
+                    // 订阅者处理太慢时跳过被覆盖的事件
+                    Err(RecvError::Lagged(_)) => continue,
+                },
+                _ = &mut shutdown => break,
+            };
+            if subscription.matches(&event) {
+                yield event.to_sse();
+            }
+        }
+    })
+}
+
+#[get("/daily")]
+async fn daily_item(
+    state: &State<AppState>,
+) -> Result<Json<DailyResponse>, status::Custom<Json<ErrorResponse>>> {
+    match get_daily_item(state).await {
+        Ok(Some(daily)) => Ok(Json(daily)),
+        Ok(None) => {
+            let error_response = ErrorResponse {
+                error: "无法获取数据".to_string(),
+                code: "NO_DATA".to_string(),
+                fields: None,
+            };
+            Err(status::Custom(Status::NotFound, Json(error_response)))
+        }
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/daily/pin", data = "<pin_request>")]
+async fn pin_daily(
+    pin_request: Json<DailyPinRequest>,
+    state: &State<AppState>,
+) -> Result<Json<serde_json::Value>, status::Custom<Json<ErrorResponse>>> {
+    let request = pin_request.into_inner();
+    let uuid = request.uuid.clone();
+    match pin_daily_item(state, request).await {
+        Ok(date) => Ok(Json(
+            serde_json::json!({"success": true, "message": "指定成功", "date": date, "uuid": uuid}),
+        )),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[get("/daily/archive?<query..>")]
+async fn daily_archive(
+    query: DailyArchiveQuery,
+    state: &State<AppState>,
+) -> Result<Json<Vec<DailyArchiveEntry>>, status::Custom<Json<ErrorResponse>>> {
+    match get_daily_archive(state, &query).await {
+        Ok(entries) => Ok(Json(entries)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
     }
 }
 
@@ -64,6 +229,472 @@ async fn submit_item(
     }
 }
 
+#[get("/hitokoto?<query..>")]
+async fn list_hitokoto(
+    query: ListItemsQuery,
+    state: &State<AppState>,
+) -> Result<Json<ItemPage>, status::Custom<Json<ErrorResponse>>> {
+    match list_items(state, &query).await {
+        Ok(page) => Ok(Json(page)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/hitokoto/<uuid>/edit", data = "<edit_request>")]
+async fn edit_item_endpoint(
+    uuid: String,
+    edit_request: Json<EditHitokotoRequest>,
+    state: &State<AppState>,
+) -> Result<Json<HitokotoItem>, status::Custom<Json<ErrorResponse>>> {
+    match edit_item(state, &uuid, edit_request.into_inner()).await {
+        Ok(item) => Ok(Json(item)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[get("/hitokoto/<uuid>/history")]
+async fn item_history(
+    uuid: String,
+    state: &State<AppState>,
+) -> Result<Json<Vec<Revision>>, status::Custom<Json<ErrorResponse>>> {
+    match get_item_history(state, &uuid).await {
+        Ok(history) => Ok(Json(history)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/hitokoto/<uuid>/revert", data = "<revert_request>")]
+async fn revert_item_endpoint(
+    uuid: String,
+    revert_request: Json<RevertRequest>,
+    state: &State<AppState>,
+) -> Result<Json<HitokotoItem>, status::Custom<Json<ErrorResponse>>> {
+    let request = revert_request.into_inner();
+    match revert_item(state, &uuid, request.user_id, request.revision).await {
+        Ok(item) => Ok(Json(item)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/hitokoto/<uuid>/like", data = "<like_request>")]
+async fn like_item(
+    uuid: String,
+    like_request: Json<LikeRequest>,
+    state: &State<AppState>,
+) -> Result<Json<LikeResponse>, status::Custom<Json<ErrorResponse>>> {
+    match set_item_like(state, &uuid, like_request.user_id, true).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/hitokoto/<uuid>/unlike", data = "<like_request>")]
+async fn unlike_item(
+    uuid: String,
+    like_request: Json<LikeRequest>,
+    state: &State<AppState>,
+) -> Result<Json<LikeResponse>, status::Custom<Json<ErrorResponse>>> {
+    match set_item_like(state, &uuid, like_request.user_id, false).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/hitokoto/<uuid>/tags", data = "<tag_request>")]
+async fn add_tags_endpoint(
+    uuid: String,
+    tag_request: Json<TagRequest>,
+    state: &State<AppState>,
+) -> Result<Json<HitokotoItem>, status::Custom<Json<ErrorResponse>>> {
+    let request = tag_request.into_inner();
+    match update_item_tags(state, &uuid, request.user_id, &request.tags, false).await {
+        Ok(item) => Ok(Json(item)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/hitokoto/<uuid>/tags/remove", data = "<tag_request>")]
+async fn remove_tags_endpoint(
+    uuid: String,
+    tag_request: Json<TagRequest>,
+    state: &State<AppState>,
+) -> Result<Json<HitokotoItem>, status::Custom<Json<ErrorResponse>>> {
+    let request = tag_request.into_inner();
+    match update_item_tags(state, &uuid, request.user_id, &request.tags, true).await {
+        Ok(item) => Ok(Json(item)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[get("/tags?<query..>")]
+async fn tag_cloud(query: TagListQuery, state: &State<AppState>) -> Json<Vec<TagCount>> {
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    Json(get_tag_counts(state, query.prefix.as_deref(), limit).await)
+}
+
+#[get("/tags/suggest?<query..>")]
+async fn tag_suggest(query: TagListQuery, state: &State<AppState>) -> Json<Vec<TagCount>> {
+    let limit = query.limit.unwrap_or(10).clamp(1, 100);
+    Json(
+        get_tag_counts(
+            state,
+            Some(query.prefix.as_deref().unwrap_or_default()),
+            limit,
+        )
+        .await,
+    )
+}
+
+#[get("/moderation/pending?<user_id>")]
+async fn pending_items(
+    user_id: u32,
+    state: &State<AppState>,
+) -> Result<Json<Vec<HitokotoItem>>, status::Custom<Json<ErrorResponse>>> {
+    match get_pending_items(state, user_id).await {
+        Ok(items) => Ok(Json(items)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/moderation/<uuid>/approve", data = "<moderation_request>")]
+async fn approve_item(
+    uuid: String,
+    moderation_request: Json<ModerationRequest>,
+    state: &State<AppState>,
+) -> Result<Json<HitokotoItem>, status::Custom<Json<ErrorResponse>>> {
+    let request = moderation_request.into_inner();
+    match set_item_status(state, &uuid, request.user_id, ItemStatus::Approved).await {
+        Ok(item) => Ok(Json(item)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/moderation/<uuid>/reject", data = "<moderation_request>")]
+async fn reject_item(
+    uuid: String,
+    moderation_request: Json<ModerationRequest>,
+    state: &State<AppState>,
+) -> Result<Json<HitokotoItem>, status::Custom<Json<ErrorResponse>>> {
+    let request = moderation_request.into_inner();
+    match set_item_status(state, &uuid, request.user_id, ItemStatus::Rejected).await {
+        Ok(item) => Ok(Json(item)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/admin/sensitive/reload", data = "<reload_request>")]
+async fn reload_sensitive_endpoint(
+    reload_request: Json<SensitiveReloadRequest>,
+    state: &State<AppState>,
+) -> Result<Json<serde_json::Value>, status::Custom<Json<ErrorResponse>>> {
+    match reload_sensitive_words(state, reload_request.user_id).await {
+        Ok(count) => Ok(Json(
+            serde_json::json!({"success": true, "message": "敏感词词典已重新加载", "count": count}),
+        )),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/admin/sensitive/test", data = "<test_request>")]
+async fn test_sensitive_endpoint(
+    test_request: Json<SensitiveTestRequest>,
+    state: &State<AppState>,
+) -> Result<Json<SensitiveTestResponse>, status::Custom<Json<ErrorResponse>>> {
+    let request = test_request.into_inner();
+    match test_sensitive_text(state, request.user_id, &request.text).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/admin/webhooks", data = "<webhook_request>")]
+async fn register_webhook_endpoint(
+    webhook_request: Json<NewWebhookRequest>,
+    state: &State<AppState>,
+) -> Result<Json<Webhook>, status::Custom<Json<ErrorResponse>>> {
+    match register_webhook(state, webhook_request.into_inner()).await {
+        Ok(webhook) => Ok(Json(webhook)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[get("/admin/webhooks?<user_id>")]
+async fn list_webhooks_endpoint(
+    user_id: u32,
+    state: &State<AppState>,
+) -> Result<Json<Vec<Webhook>>, status::Custom<Json<ErrorResponse>>> {
+    match list_webhooks(state, user_id).await {
+        Ok(webhooks) => Ok(Json(webhooks)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[delete("/admin/webhooks/<webhook_id>?<user_id>")]
+async fn delete_webhook_endpoint(
+    webhook_id: String,
+    user_id: u32,
+    state: &State<AppState>,
+) -> Result<Json<serde_json::Value>, status::Custom<Json<ErrorResponse>>> {
+    match delete_webhook(state, &webhook_id, user_id).await {
+        Ok(webhook) => Ok(Json(
+            serde_json::json!({"success": true, "message": "删除成功", "webhook_id": webhook.webhook_id}),
+        )),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[get("/admin/webhooks/<webhook_id>/deliveries?<query..>")]
+async fn webhook_deliveries(
+    webhook_id: String,
+    query: DeliveryLogQuery,
+    state: &State<AppState>,
+) -> Result<Json<Vec<Delivery>>, status::Custom<Json<ErrorResponse>>> {
+    match get_webhook_deliveries(state, &webhook_id, &query).await {
+        Ok(deliveries) => Ok(Json(deliveries)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/hitokoto/<uuid>/report", data = "<report_request>")]
+async fn report_item(
+    uuid: String,
+    report_request: Json<ReportRequest>,
+    state: &State<AppState>,
+) -> Result<Json<Report>, status::Custom<Json<ErrorResponse>>> {
+    match report_target(
+        state,
+        ReportTarget::Hitokoto,
+        &uuid,
+        report_request.into_inner(),
+    )
+    .await
+    {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/collection/<collection_id>/report", data = "<report_request>")]
+async fn report_collection(
+    collection_id: String,
+    report_request: Json<ReportRequest>,
+    state: &State<AppState>,
+) -> Result<Json<Report>, status::Custom<Json<ErrorResponse>>> {
+    let request = report_request.into_inner();
+    match report_target(state, ReportTarget::Collection, &collection_id, request).await {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[get("/moderation/reports?<query..>")]
+async fn report_inbox(
+    query: ReportInboxQuery,
+    state: &State<AppState>,
+) -> Result<Json<Vec<Report>>, status::Custom<Json<ErrorResponse>>> {
+    match get_reports(state, &query).await {
+        Ok(reports) => Ok(Json(reports)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/moderation/reports/<report_id>/resolve", data = "<handle_request>")]
+async fn resolve_report(
+    report_id: String,
+    handle_request: Json<HandleReportRequest>,
+    state: &State<AppState>,
+) -> Result<Json<Report>, status::Custom<Json<ErrorResponse>>> {
+    match handle_report(state, &report_id, handle_request.into_inner(), true).await {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/moderation/reports/<report_id>/dismiss", data = "<handle_request>")]
+async fn dismiss_report(
+    report_id: String,
+    handle_request: Json<HandleReportRequest>,
+    state: &State<AppState>,
+) -> Result<Json<Report>, status::Custom<Json<ErrorResponse>>> {
+    match handle_report(state, &report_id, handle_request.into_inner(), false).await {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[get("/categories")]
+async fn list_categories(state: &State<AppState>) -> Json<Vec<CategoryWithCount>> {
+    Json(get_categories_with_counts(state).await)
+}
+
+#[get("/search?<query..>")]
+async fn search(
+    query: SearchQuery,
+    state: &State<AppState>,
+) -> Result<Json<SearchResponse>, status::Custom<Json<ErrorResponse>>> {
+    if query.q.trim().is_empty() {
+        let error_response = ErrorResponse {
+            error: "搜索关键词不能为空".to_string(),
+            code: "VALIDATION_ERROR".to_string(),
+            fields: None,
+        };
+        return Err(status::Custom(Status::BadRequest, Json(error_response)));
+    }
+    Ok(Json(search_items(state, &query).await))
+}
+
+#[post("/work/create", data = "<source_request>")]
+async fn create_work(
+    source_request: Json<NewSourceRequest>,
+    state: &State<AppState>,
+) -> Result<Json<SourceEntity>, status::Custom<Json<ErrorResponse>>> {
+    match create_source(state, SourceKind::Work, source_request.into_inner()).await {
+        Ok(entity) => Ok(Json(entity)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[get("/work/<id>")]
+async fn get_work(
+    id: String,
+    state: &State<AppState>,
+) -> Result<Json<SourceWithItems>, status::Custom<Json<ErrorResponse>>> {
+    match get_source_with_items(state, SourceKind::Work, &id).await {
+        Ok(source) => Ok(Json(source)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/work/<id>/merge", data = "<merge_request>")]
+async fn merge_work(
+    id: String,
+    merge_request: Json<MergeSourceRequest>,
+    state: &State<AppState>,
+) -> Result<Json<SourceEntity>, status::Custom<Json<ErrorResponse>>> {
+    match merge_sources(state, SourceKind::Work, &id, merge_request.into_inner()).await {
+        Ok(entity) => Ok(Json(entity)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/author/create", data = "<source_request>")]
+async fn create_author(
+    source_request: Json<NewSourceRequest>,
+    state: &State<AppState>,
+) -> Result<Json<SourceEntity>, status::Custom<Json<ErrorResponse>>> {
+    match create_source(state, SourceKind::Author, source_request.into_inner()).await {
+        Ok(entity) => Ok(Json(entity)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[get("/author/<id>")]
+async fn get_author(
+    id: String,
+    state: &State<AppState>,
+) -> Result<Json<SourceWithItems>, status::Custom<Json<ErrorResponse>>> {
+    match get_source_with_items(state, SourceKind::Author, &id).await {
+        Ok(source) => Ok(Json(source)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/author/<id>/merge", data = "<merge_request>")]
+async fn merge_author(
+    id: String,
+    merge_request: Json<MergeSourceRequest>,
+    state: &State<AppState>,
+) -> Result<Json<SourceEntity>, status::Custom<Json<ErrorResponse>>> {
+    match merge_sources(state, SourceKind::Author, &id, merge_request.into_inner()).await {
+        Ok(entity) => Ok(Json(entity)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
 #[post("/register", data = "<user_request>")]
 async fn register_user(
     state: &State<AppState>,
@@ -101,19 +732,125 @@ async fn get_user(
             let error_response = ErrorResponse {
                 error: format!("用户ID {} 不存在", user_id),
                 code: "USER_NOT_FOUND".to_string(),
+                fields: None,
             };
             Err(status::Custom(Status::NotFound, Json(error_response)))
         }
     }
 }
 
+#[get("/user/<user_id>/likes")]
+async fn user_likes(
+    user_id: u32,
+    state: &State<AppState>,
+) -> Result<Json<Vec<HitokotoItem>>, status::Custom<Json<ErrorResponse>>> {
+    match get_user_likes(state, user_id).await {
+        Ok(items) => Ok(Json(items)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+// 订阅源的公共处理：读取内容并按条件请求头决定是否返回 304
+async fn feed_response(
+    state: &State<AppState>,
+    scope: FeedScope,
+    format: FeedFormat,
+    conditional: ConditionalHeaders,
+) -> Result<FeedResponse, status::Custom<Json<ErrorResponse>>> {
+    match get_feed(state, &scope).await {
+        Ok(feed) => Ok(FeedResponse::new(&feed, format, &conditional)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[get("/feed.rss")]
+async fn feed_rss(
+    conditional: ConditionalHeaders,
+    state: &State<AppState>,
+) -> Result<FeedResponse, status::Custom<Json<ErrorResponse>>> {
+    feed_response(state, FeedScope::All, FeedFormat::Rss, conditional).await
+}
+
+#[get("/feed.atom")]
+async fn feed_atom(
+    conditional: ConditionalHeaders,
+    state: &State<AppState>,
+) -> Result<FeedResponse, status::Custom<Json<ErrorResponse>>> {
+    feed_response(state, FeedScope::All, FeedFormat::Atom, conditional).await
+}
+
+#[get("/user/<user_id>/feed.rss")]
+async fn user_feed_rss(
+    user_id: u32,
+    conditional: ConditionalHeaders,
+    state: &State<AppState>,
+) -> Result<FeedResponse, status::Custom<Json<ErrorResponse>>> {
+    feed_response(
+        state,
+        FeedScope::User(user_id),
+        FeedFormat::Rss,
+        conditional,
+    )
+    .await
+}
+
+#[get("/user/<user_id>/feed.atom")]
+async fn user_feed_atom(
+    user_id: u32,
+    conditional: ConditionalHeaders,
+    state: &State<AppState>,
+) -> Result<FeedResponse, status::Custom<Json<ErrorResponse>>> {
+    feed_response(
+        state,
+        FeedScope::User(user_id),
+        FeedFormat::Atom,
+        conditional,
+    )
+    .await
+}
+
+#[get("/collection/<collection_id>/feed.rss")]
+async fn collection_feed_rss(
+    collection_id: &str,
+    conditional: ConditionalHeaders,
+    state: &State<AppState>,
+) -> Result<FeedResponse, status::Custom<Json<ErrorResponse>>> {
+    let scope = FeedScope::Collection(collection_id.to_string());
+    feed_response(state, scope, FeedFormat::Rss, conditional).await
+}
+
+#[get("/collection/<collection_id>/feed.atom")]
+async fn collection_feed_atom(
+    collection_id: &str,
+    conditional: ConditionalHeaders,
+    state: &State<AppState>,
+) -> Result<FeedResponse, status::Custom<Json<ErrorResponse>>> {
+    let scope = FeedScope::Collection(collection_id.to_string());
+    feed_response(state, scope, FeedFormat::Atom, conditional).await
+}
+
 #[post("/collection/create", data = "<new_collection>")]
 async fn create_collection_endpoint(
     new_collection: Json<NewCollectionRequest>,
     state: &State<AppState>,
 ) -> Result<Json<Collection>, status::Custom<Json<ErrorResponse>>> {
     let request = new_collection.into_inner();
-    match create_collection(state, request.user_id, request.title, request.description).await {
+    match create_collection(
+        state,
+        request.user_id,
+        request.title,
+        request.description,
+        request.query,
+        request.parent_id,
+    )
+    .await
+    {
         Ok(collection) => Ok(Json(collection)),
         Err(e) => {
             let error_response = e.to_response();
@@ -125,13 +862,128 @@ async fn create_collection_endpoint(
 #[post("/collection/<collection_id>/add", data = "<add_request>")]
 async fn add_to_collection_endpoint(
     collection_id: String,
-    add_request: Json<AddToCollectionRequest>,
+    add_request: Json<CollectionEntriesRequest>,
     state: &State<AppState>,
 ) -> Result<Json<serde_json::Value>, status::Custom<Json<ErrorResponse>>> {
     let request = add_request.into_inner();
-    match add_hitokoto_to_collection(state, collection_id, request.hitokoto_uuid).await {
-        Ok(()) => Ok(Json(
-            serde_json::json!({"success": true, "message": "添加成功"}),
+    let user_id = request.user_id;
+    match add_hitokoto_to_collection(state, collection_id, user_id, request.into_uuids()).await {
+        Ok(result) => Ok(Json(serde_json::json!({
+            "success": true,
+            "message": "添加成功",
+            "count": result.added.len(),
+            "added": result.added,
+            "skipped_duplicates": result.skipped_duplicates,
+        }))),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/collection/<collection_id>/remove", data = "<remove_request>")]
+async fn remove_from_collection_endpoint(
+    collection_id: String,
+    remove_request: Json<CollectionEntriesRequest>,
+    state: &State<AppState>,
+) -> Result<Json<serde_json::Value>, status::Custom<Json<ErrorResponse>>> {
+    let request = remove_request.into_inner();
+    let user_id = request.user_id;
+    match remove_hitokoto_from_collection(state, collection_id, user_id, request.into_uuids()).await
+    {
+        Ok(count) => Ok(Json(
+            serde_json::json!({"success": true, "message": "移除成功", "count": count}),
+        )),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/collection/<collection_id>/annotate", data = "<annotate_request>")]
+async fn annotate_collection_entry_endpoint(
+    collection_id: String,
+    annotate_request: Json<AnnotateEntryRequest>,
+    state: &State<AppState>,
+) -> Result<Json<CollectionEntry>, status::Custom<Json<ErrorResponse>>> {
+    let request = annotate_request.into_inner();
+    match annotate_collection_entry(
+        state,
+        collection_id,
+        request.user_id,
+        request.hitokoto_uuid,
+        request.note,
+        request.highlight,
+    )
+    .await
+    {
+        Ok(entry) => Ok(Json(entry)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/collection/<collection_id>/fork", data = "<fork_request>")]
+async fn fork_collection_endpoint(
+    collection_id: String,
+    fork_request: Json<ForkCollectionRequest>,
+    state: &State<AppState>,
+) -> Result<status::Custom<Json<Collection>>, status::Custom<Json<ErrorResponse>>> {
+    let request = fork_request.into_inner();
+    match fork_collection(state, collection_id, request.user_id).await {
+        Ok(collection) => Ok(status::Custom(Status::Created, Json(collection))),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/collection/<collection_id>/snapshot?<user_id>")]
+async fn snapshot_collection_endpoint(
+    collection_id: String,
+    user_id: u32,
+    state: &State<AppState>,
+) -> Result<Json<Collection>, status::Custom<Json<ErrorResponse>>> {
+    match snapshot_collection(state, collection_id, user_id).await {
+        Ok(collection) => Ok(Json(collection)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[post("/collection/<collection_id>/move", data = "<move_request>")]
+async fn move_collection_endpoint(
+    collection_id: String,
+    move_request: Json<MoveCollectionRequest>,
+    state: &State<AppState>,
+) -> Result<Json<Collection>, status::Custom<Json<ErrorResponse>>> {
+    let request = move_request.into_inner();
+    match move_collection(state, collection_id, request.parent_id, request.user_id).await {
+        Ok(collection) => Ok(Json(collection)),
+        Err(e) => {
+            let error_response = e.to_response();
+            Err(status::Custom(e.status_code(), Json(error_response)))
+        }
+    }
+}
+
+#[delete("/collection/<collection_id>?<user_id>&<mode>")]
+async fn delete_collection_endpoint(
+    collection_id: String,
+    user_id: u32,
+    mode: Option<DeleteMode>,
+    state: &State<AppState>,
+) -> Result<Json<serde_json::Value>, status::Custom<Json<ErrorResponse>>> {
+    match delete_collection(state, collection_id, mode.unwrap_or_default(), user_id).await {
+        Ok(deleted) => Ok(Json(
+            serde_json::json!({"success": true, "message": "删除成功", "deleted": deleted}),
         )),
         Err(e) => {
             let error_response = e.to_response();
@@ -145,23 +997,92 @@ fn rocket() -> _ {
     // 创建 Tokio 运行时来处理异步初始化
     let rt = tokio::runtime::Runtime::new().expect("创建 Tokio 运行时失败");
 
+    // 读取 Rocket.toml 中的应用配置，缺省时使用默认值
+    let rocket = rocket::build();
+    let config: AppConfig = if rocket.figment().contains("pencil") {
+        match rocket.figment().extract_inner("pencil") {
+            Ok(config) => config,
+            Err(e) => panic!("读取配置失败: {e}"),
+        }
+    } else {
+        AppConfig::default()
+    };
+
     // 启动时加载数据到内存
     let app_state = rt.block_on(async {
-        match load_data().await {
+        match load_data(config).await {
             Ok(state) => state,
             Err(e) => panic!("加载数据失败: {e}"),
         }
     });
 
-    rocket::build().manage(app_state).mount(
+    // 启动后在后台投递回调，服务关闭时停止
+    let webhooks = app_state.webhooks.clone();
+    let webhook_worker = AdHoc::on_liftoff("Webhook 投递", move |rocket| {
+        let shutdown = rocket.shutdown();
+        Box::pin(async move {
+            rocket::tokio::spawn(webhooks.run(shutdown));
+        })
+    });
+
+    rocket.manage(app_state).attach(webhook_worker).mount(
         "/",
         routes![
             get_item,
+            events,
+            daily_item,
+            pin_daily,
+            daily_archive,
             submit_item,
+            list_hitokoto,
+            list_categories,
+            edit_item_endpoint,
+            item_history,
+            revert_item_endpoint,
+            like_item,
+            unlike_item,
+            add_tags_endpoint,
+            remove_tags_endpoint,
+            tag_cloud,
+            tag_suggest,
+            pending_items,
+            approve_item,
+            reject_item,
+            report_item,
+            report_collection,
+            report_inbox,
+            resolve_report,
+            dismiss_report,
+            reload_sensitive_endpoint,
+            test_sensitive_endpoint,
+            register_webhook_endpoint,
+            list_webhooks_endpoint,
+            delete_webhook_endpoint,
+            webhook_deliveries,
+            search,
+            create_work,
+            get_work,
+            merge_work,
+            create_author,
+            get_author,
+            merge_author,
             register_user,
             get_user,
+            user_likes,
+            feed_rss,
+            feed_atom,
+            user_feed_rss,
+            user_feed_atom,
+            collection_feed_rss,
+            collection_feed_atom,
             create_collection_endpoint,
-            add_to_collection_endpoint
+            add_to_collection_endpoint,
+            remove_from_collection_endpoint,
+            annotate_collection_entry_endpoint,
+            fork_collection_endpoint,
+            snapshot_collection_endpoint,
+            move_collection_endpoint,
+            delete_collection_endpoint
         ],
     )
 }
@@ -0,0 +1,291 @@
+use crate::config::SvgConfig;
+use crate::error::{AppError, AppResult};
+use crate::item::{HitokotoItem, RandomItemResult};
+use rocket::FromFormField;
+use rocket::http::{Accept, ContentType, MediaType};
+
+// JavaScript 输出中 CSS 选择器的最大长度
+pub const MAX_SELECTOR_LENGTH: usize = 100;
+
+// 不应出现在行首的标点，换行时允许它们略微超出行宽
+const NO_BREAK_BEFORE: &str = "，。、；：！？）》」』】〉…—,.;:!?)]}'\"";
+
+// /get 的输出格式
+#[derive(FromFormField, Clone, Copy, Default, PartialEq, Debug)]
+pub enum OutputFormat {
+    // JSON 对象或数组
+    #[default]
+    #[field(value = "json")]
+    Json,
+    // 纯文本，每行一条
+    #[field(value = "text")]
+    Text,
+    // 写入页面的 JavaScript 片段
+    #[field(value = "js")]
+    Js,
+    // 带样式的 SVG 卡片
+    #[field(value = "svg")]
+    Svg,
+}
+
+impl OutputFormat {
+    // 按 Accept 头选择输出格式，从权重最高的媒体类型开始找第一个能识别的，都不能识别时使用 JSON
+    pub fn from_accept(accept: &Accept) -> Self {
+        let mut media_types: Vec<_> = accept.iter().collect();
+        media_types.sort_by(|a, b| b.weight_or(1.0).total_cmp(&a.weight_or(1.0)));
+        media_types
+            .into_iter()
+            .find_map(|media_type| Self::from_media_type(media_type.media_type()))
+            .unwrap_or_default()
+    }
+
+    fn from_media_type(media_type: &MediaType) -> Option<Self> {
+        if media_type.is_json() {
+            Some(OutputFormat::Json)
+        } else if media_type.is_plain() {
+            Some(OutputFormat::Text)
+        } else if media_type.sub() == "javascript" {
+            // text/javascript 与 application/javascript
+            Some(OutputFormat::Js)
+        } else if media_type.is_svg() {
+            Some(OutputFormat::Svg)
+        } else {
+            None
+        }
+    }
+}
+
+// 非 JSON 输出的渲染选项
+pub struct RenderOptions {
+    pub format: OutputFormat,
+    pub select: Option<String>, // JavaScript 输出时写入的元素，省略时使用 document.write
+    pub with_from: Option<bool>, // 是否附带出处，文本和 JavaScript 默认不附带，SVG 默认附带
+}
+
+impl RenderOptions {
+    // 在选取条目之前校验选项，JavaScript 和 SVG 只能输出单条
+    pub fn validate(&self, count: Option<usize>) -> AppResult<()> {
+        if matches!(self.format, OutputFormat::Js | OutputFormat::Svg)
+            && count.is_some_and(|count| count > 1)
+        {
+            return Err(AppError::Validation(
+                "JavaScript 和 SVG 格式一次只能获取一条".to_string(),
+            ));
+        }
+        if let Some(select) = &self.select
+            && select.chars().count() > MAX_SELECTOR_LENGTH
+        {
+            return Err(AppError::Validation(format!(
+                "select 不能超过{}个字符",
+                MAX_SELECTOR_LENGTH
+            )));
+        }
+        Ok(())
+    }
+
+    // 按格式渲染选取结果，返回内容类型和正文
+    pub fn render(&self, result: &RandomItemResult, svg: &SvgConfig) -> (ContentType, String) {
+        let items: Vec<&HitokotoItem> = match result {
+            RandomItemResult::One(item) => vec![&item.item],
+            RandomItemResult::Many(items) => items.iter().map(|item| &item.item).collect(),
+        };
+        match self.format {
+            OutputFormat::Json => (
+                ContentType::JSON,
+                serde_json::to_string(result).unwrap_or_default(),
+            ),
+            OutputFormat::Text => {
+                let with_from = self.with_from.unwrap_or(false);
+                let lines: Vec<String> = items
+                    .iter()
+                    .map(|item| render_line(item, with_from))
+                    .collect();
+                (ContentType::Plain, lines.join("\n"))
+            }
+            OutputFormat::Js => (
+                ContentType::JavaScript,
+                render_js(
+                    items[0],
+                    self.select.as_deref(),
+                    self.with_from.unwrap_or(false),
+                ),
+            ),
+            OutputFormat::Svg => (
+                ContentType::SVG,
+                render_svg(items[0], svg, self.with_from.unwrap_or(true)),
+            ),
+        }
+    }
+}
+
+// 出处，如 “—— 鲁迅「呐喊」”
+pub fn from_line(item: &HitokotoItem) -> String {
+    match item.from_who.as_deref().filter(|who| !who.is_empty()) {
+        Some(who) => format!("—— {}「{}」", who, item.from),
+        None => format!("—— {}", item.from),
+    }
+}
+
+// 单行文本，可附带出处
+fn render_line(item: &HitokotoItem, with_from: bool) -> String {
+    if with_from {
+        format!("{} {}", item.hitokoto, from_line(item))
+    } else {
+        item.hitokoto.clone()
+    }
+}
+
+// 转为可以直接放进 <script> 的 JavaScript 字符串字面量
+fn js_string(text: &str) -> String {
+    serde_json::to_string(text)
+        .unwrap_or_default()
+        .replace("</", "<\\/")
+        .replace('\u{2028}', "\\u2028")
+        .replace('\u{2029}', "\\u2029")
+}
+
+// 转义 HTML 和 XML 中的特殊字符
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 提供选择器时写入第一个匹配元素的文本，否则用 document.write 写在脚本所在位置
+pub fn render_js(item: &HitokotoItem, select: Option<&str>, with_from: bool) -> String {
+    let line = render_line(item, with_from);
+    match select {
+        Some(select) => format!(
+            "(function () {{\n  var element = document.querySelector({});\n  if (element) {{\n    element.textContent = {};\n  }}\n}})();\n",
+            js_string(select),
+            js_string(&line)
+        ),
+        None => format!("document.write({});\n", js_string(&escape_xml(&line))),
+    }
+}
+
+// 字符的大致显示宽度，单位为字号（em）
+// 中日韩文字、全角符号和 emoji 按整个字宽计算，其余按半个多字宽计算
+fn char_width(c: char) -> f64 {
+    let wide = matches!(c as u32,
+        0x1100..=0x115F
+        | 0x2E80..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1FAFF
+        | 0x20000..=0x3FFFD);
+    if wide { 1.0 } else { 0.55 }
+}
+
+// 按最大行宽（单位为字号）折行
+// 中日韩文字可在任意字符间断开，其他文字在空白处断开，过长的单词按字符断开
+pub fn wrap_text(text: &str, max_width: f64) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        // 拆分为可断开的片段：单个宽字符、单个空白或连续的其他字符
+        let mut tokens: Vec<String> = Vec::new();
+        let mut in_word = false;
+        for c in paragraph.chars() {
+            let narrow = !c.is_whitespace() && char_width(c) < 1.0;
+            match tokens.last_mut() {
+                Some(last) if narrow && in_word => last.push(c),
+                _ => tokens.push(c.to_string()),
+            }
+            in_word = narrow;
+        }
+
+        let mut line = String::new();
+        let mut width = 0.0;
+        for token in tokens {
+            let token_width: f64 = token.chars().map(char_width).sum();
+            let no_break = token.chars().all(|c| NO_BREAK_BEFORE.contains(c));
+            if width + token_width > max_width && !line.trim().is_empty() && !no_break {
+                lines.push(line.trim_end().to_string());
+                line.clear();
+                width = 0.0;
+            }
+            if line.is_empty() && token.trim().is_empty() {
+                continue;
+            }
+            if token_width > max_width {
+                for c in token.chars() {
+                    if width + char_width(c) > max_width && !line.is_empty() {
+                        lines.push(std::mem::take(&mut line));
+                        width = 0.0;
+                    }
+                    line.push(c);
+                    width += char_width(c);
+                }
+            } else {
+                line.push_str(&token);
+                width += token_width;
+            }
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    lines.retain(|line| !line.is_empty());
+    lines
+}
+
+// 带背景的 SVG 卡片，正文按卡片宽度折行，出处右对齐显示在末尾
+pub fn render_svg(item: &HitokotoItem, config: &SvgConfig, with_from: bool) -> String {
+    let font_size = f64::from(config.font_size.max(1));
+    let padding = f64::from(config.padding);
+    let width = f64::from(config.width).max(padding * 2.0 + font_size);
+    let line_height = font_size * config.line_height;
+    let lines = wrap_text(&item.hitokoto, (width - padding * 2.0) / font_size);
+
+    let mut body = String::new();
+    let mut baseline = padding + font_size;
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            baseline += line_height;
+        }
+        body.push_str(&format!(
+            "    <tspan x=\"{}\" y=\"{}\">{}</tspan>\n",
+            padding,
+            baseline,
+            escape_xml(line)
+        ));
+    }
+    let mut from = String::new();
+    if with_from {
+        baseline += line_height;
+        from = format!(
+            "  <text x=\"{}\" y=\"{}\" font-family=\"{}\" font-size=\"{}\" fill=\"{}\" text-anchor=\"end\">{}</text>\n",
+            width - padding,
+            baseline,
+            escape_xml(&config.font_family),
+            font_size * 0.8,
+            escape_xml(&config.from_color),
+            escape_xml(&from_line(item))
+        );
+    }
+    let height = (baseline + padding + font_size * 0.3).ceil();
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" role=\"img\" aria-label=\"{label}\">\n  <rect width=\"100%\" height=\"100%\" rx=\"{radius}\" fill=\"{background}\"/>\n  <text font-family=\"{font}\" font-size=\"{size}\" fill=\"{color}\">\n{body}  </text>\n{from}</svg>\n",
+        w = width,
+        h = height,
+        label = escape_xml(&render_line(item, with_from)),
+        radius = config.border_radius,
+        background = escape_xml(&config.background),
+        font = escape_xml(&config.font_family),
+        size = font_size,
+        color = escape_xml(&config.color),
+        body = body,
+        from = from,
+    )
+}
@@ -0,0 +1,69 @@
+use crate::config::SvgConfig;
+use crate::render::{OutputFormat, render_js, render_svg, wrap_text};
+use crate::test_support::sample_item;
+use rocket::http::Accept;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_text_mixed_scripts() {
+        // 测试中文按字符折行、英文按单词折行，行首不出现句读，过长的单词按字符断开
+        assert_eq!(
+            wrap_text("一二三四五六七八", 3.0),
+            ["一二三", "四五六", "七八"]
+        );
+        assert_eq!(wrap_text("一二三，四五", 3.0), ["一二三，", "四五"]);
+        assert_eq!(
+            wrap_text("hello brave new world", 4.0),
+            ["hello", "brave", "new", "world"]
+        );
+        assert_eq!(wrap_text("abcdefghij", 3.0), ["abcde", "fghij"]);
+        assert_eq!(
+            wrap_text("中文 and English", 4.0),
+            ["中文", "and", "English"]
+        );
+    }
+
+    #[test]
+    fn test_output_format_from_accept() {
+        // 测试按 Accept 头的权重选择第一个能识别的格式
+        let accept = |header: &str| OutputFormat::from_accept(&Accept::from_str(header).unwrap());
+        assert_eq!(
+            accept("image/avif,image/webp,image/svg+xml,image/*,*/*;q=0.8"),
+            OutputFormat::Svg
+        );
+        assert_eq!(
+            accept("text/plain;q=0.5, application/json"),
+            OutputFormat::Json
+        );
+        assert_eq!(accept("text/plain"), OutputFormat::Text);
+        assert_eq!(accept("application/javascript"), OutputFormat::Js);
+        assert_eq!(accept("text/html,*/*"), OutputFormat::Json);
+    }
+
+    #[test]
+    fn test_rendered_output_is_escaped() {
+        // 测试 JavaScript 和 SVG 输出中的内容被正确转义
+        let mut item = sample_item("<b>\"一言\"</b> & </script>");
+        item.from = "呐喊".to_string();
+        item.from_who = Some("鲁迅".to_string());
+        let written = render_js(&item, None, true);
+        assert!(written.starts_with("document.write("));
+        assert!(written.contains("&lt;b&gt;"));
+        assert!(written.contains("—— 鲁迅「呐喊」"));
+        assert!(!written.contains("</"));
+
+        let injected = render_js(&item, Some("#quote"), false);
+        assert!(injected.contains("document.querySelector(\"#quote\")"));
+        assert!(injected.contains("<b>"));
+        assert!(!injected.contains("</"));
+
+        let svg = render_svg(&item, &SvgConfig::default(), true);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.contains("&lt;b&gt;&quot;一言&quot;&lt;/b&gt; &amp;"));
+        assert!(svg.contains("text-anchor=\"end\">—— 鲁迅「呐喊」</text>"));
+    }
+}
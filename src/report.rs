@@ -0,0 +1,127 @@
+use rocket::{FromForm, FromFormField};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+// 举报原因
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportReason {
+    Spam,      // 垃圾内容或广告
+    Offensive, // 冒犯、骚扰或仇恨内容
+    Copyright, // 侵犯版权
+    Incorrect, // 出处或内容错误
+    Other,
+}
+
+// 被举报的对象类型
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportTarget {
+    Hitokoto,
+    Collection,
+}
+
+// 举报处理状态
+#[derive(Deserialize, Serialize, FromFormField, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportStatus {
+    // 待处理
+    #[default]
+    #[field(value = "open")]
+    Open,
+    // 举报成立
+    #[field(value = "resolved")]
+    Resolved,
+    // 举报不成立
+    #[field(value = "dismissed")]
+    Dismissed,
+}
+
+// 一条举报
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Report {
+    pub report_id: String,
+    pub target_type: ReportTarget,
+    pub target_id: String, // Hitokoto UUID 或文集 ID
+    pub reporter_id: u32,
+    pub reason: ReportReason,
+    pub detail: Option<String>, // 补充说明
+    #[serde(default)]
+    pub status: ReportStatus,
+    #[serde(default)]
+    pub hid_target: bool, // 该举报使待处理举报数达到阈值并隐藏了对象
+    pub created_at: u64,
+    #[serde(default)]
+    pub handled_by: Option<u32>, // 处理举报的版主
+}
+
+// 提交举报的请求，user_id 为举报者
+#[derive(Deserialize)]
+pub struct ReportRequest {
+    pub user_id: u32,
+    pub reason: ReportReason,
+    pub detail: Option<String>,
+}
+
+// 处理举报的请求，user_id 为操作者
+#[derive(Deserialize)]
+pub struct HandleReportRequest {
+    pub user_id: u32,
+}
+
+// 举报收件箱的查询参数
+#[derive(FromForm)]
+pub struct ReportInboxQuery {
+    pub user_id: u32,
+    pub status: Option<ReportStatus>, // 默认只列出待处理的举报
+}
+
+// 补充说明的最大长度（字符数）
+pub const MAX_REPORT_DETAIL_LENGTH: usize = 500;
+
+impl Report {
+    pub fn new(
+        target_type: ReportTarget,
+        target_id: String,
+        reporter_id: u32,
+        reason: ReportReason,
+        detail: Option<String>,
+    ) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        Report {
+            report_id: Uuid::new_v4().to_string(),
+            target_type,
+            target_id,
+            reporter_id,
+            reason,
+            detail: detail
+                .map(|detail| detail.trim().to_string())
+                .filter(|detail| !detail.is_empty()),
+            status: ReportStatus::Open,
+            hid_target: false,
+            created_at,
+            handled_by: None,
+        }
+    }
+
+    // 是否为针对指定对象的待处理举报
+    pub fn is_open_for(&self, target_type: ReportTarget, target_id: &str) -> bool {
+        self.status == ReportStatus::Open
+            && self.target_type == target_type
+            && self.target_id == target_id
+    }
+
+    // 是否为同一举报者对同一对象的待处理举报
+    pub fn is_open_duplicate_of(
+        &self,
+        target_type: ReportTarget,
+        target_id: &str,
+        reporter_id: u32,
+    ) -> bool {
+        self.is_open_for(target_type, target_id) && self.reporter_id == reporter_id
+    }
+}
@@ -0,0 +1,33 @@
+use crate::report::{Report, ReportReason, ReportStatus, ReportTarget};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_duplicate_detection() {
+        // 测试举报的补充说明规范化及按举报者去重
+        let mut report = Report::new(
+            ReportTarget::Hitokoto,
+            "uuid-1".to_string(),
+            7,
+            ReportReason::Spam,
+            Some("  ".to_string()),
+        );
+        assert_eq!(report.detail, None);
+        let hitokoto = ReportTarget::Hitokoto;
+        assert!(report.is_open_duplicate_of(hitokoto, "uuid-1", 7));
+        assert!(!report.is_open_duplicate_of(hitokoto, "uuid-1", 8));
+        assert!(!report.is_open_duplicate_of(hitokoto, "uuid-2", 7));
+        // 同 ID 的文集与 Hitokoto 是不同的对象
+        assert!(!report.is_open_for(ReportTarget::Collection, "uuid-1"));
+
+        report.status = ReportStatus::Dismissed;
+        assert!(!report.is_open_duplicate_of(hitokoto, "uuid-1", 7));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["target_type"], "hitokoto");
+        assert_eq!(json["reason"], "spam");
+        assert_eq!(json["status"], "dismissed");
+    }
+}
@@ -0,0 +1,175 @@
+use crate::error::{AppError, AppResult};
+use crate::item::{HitokotoItem, ItemStatus};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 系统自动操作（如举报达到阈值时自动隐藏）记录的操作者
+pub const SYSTEM_EDITOR_ID: u32 = 0;
+pub const SYSTEM_EDITOR: &str = "系统";
+
+// 修订记录的字段，顺序即记录中的顺序
+const TRACKED_FIELDS: [&str; 6] = ["hitokoto", "type", "from", "from_who", "tags", "status"];
+
+// 回退时还原的字段，审核状态由审核和举报流程管理，回退不改变
+const REVERTIBLE_FIELDS: [&str; 5] = ["hitokoto", "type", "from", "from_who", "tags"];
+
+// 修订的来源
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum RevisionAction {
+    Edit,   // 编辑内容
+    Tags,   // 添加或移除标签
+    Status, // 审核状态变更
+    Revert, // 回退到之前的修订
+}
+
+// 单个字段的修改
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+// Hitokoto的一次修订
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Revision {
+    pub revision: u32, // 条目内从1开始递增，0 表示提交时的原始版本
+    pub hitokoto_uuid: String,
+    pub editor_id: u32,
+    pub editor: String,
+    pub action: RevisionAction,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub reverted_to: Option<u32>, // 回退操作的目标修订
+    pub changes: Vec<FieldChange>,
+    pub created_at: u64,
+}
+
+// 编辑Hitokoto的请求，user_id 为操作者，省略的字段保持不变
+// from_who 传空字符串表示清除作者
+#[derive(Deserialize)]
+pub struct EditHitokotoRequest {
+    pub user_id: u32,
+    pub hitokoto: Option<String>,
+    #[serde(rename = "type")]
+    pub item_type: Option<String>,
+    pub from: Option<String>,
+    pub from_who: Option<String>,
+}
+
+// 回退Hitokoto的请求，user_id 为操作者
+#[derive(Deserialize)]
+pub struct RevertRequest {
+    pub user_id: u32,
+    pub revision: u32,
+}
+
+// 读取条目中某个受跟踪字段的值
+fn field_value(item: &HitokotoItem, field: &str) -> Value {
+    match field {
+        "hitokoto" => Value::from(item.hitokoto.clone()),
+        "type" => Value::from(item.item_type.clone()),
+        "from" => Value::from(item.from.clone()),
+        "from_who" => item.from_who.clone().map_or(Value::Null, Value::from),
+        "tags" => serde_json::to_value(&item.tags).unwrap_or_default(),
+        "status" => serde_json::to_value(item.status).unwrap_or_default(),
+        _ => Value::Null,
+    }
+}
+
+// 将字段值写回条目
+fn set_field_value(item: &mut HitokotoItem, field: &str, value: &Value) -> AppResult<()> {
+    let invalid = |e: serde_json::Error| {
+        AppError::Storage(format!("修订记录中的字段 {} 无法还原: {}", field, e))
+    };
+    match field {
+        "hitokoto" => {
+            item.hitokoto = serde_json::from_value(value.clone()).map_err(invalid)?;
+            item.length = item.hitokoto.chars().count() as u32;
+        }
+        "type" => item.item_type = serde_json::from_value(value.clone()).map_err(invalid)?,
+        "from" => item.from = serde_json::from_value(value.clone()).map_err(invalid)?,
+        "from_who" => item.from_who = serde_json::from_value(value.clone()).map_err(invalid)?,
+        "tags" => item.tags = serde_json::from_value(value.clone()).map_err(invalid)?,
+        "status" => {
+            item.status = serde_json::from_value::<ItemStatus>(value.clone()).map_err(invalid)?
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// 比较修改前后的条目，返回发生变化的字段
+pub fn diff_items(before: &HitokotoItem, after: &HitokotoItem) -> Vec<FieldChange> {
+    TRACKED_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let old = field_value(before, field);
+            let new = field_value(after, field);
+            (old != new).then(|| FieldChange {
+                field: field.to_string(),
+                old,
+                new,
+            })
+        })
+        .collect()
+}
+
+// 根据修订历史还原条目在指定修订之后的内容
+// 从当前状态出发，按倒序撤销目标修订之后对内容字段的每一次修改，审核状态保持当前值
+pub fn item_at_revision(
+    current: &HitokotoItem,
+    history: &[Revision],
+    revision: u32,
+) -> AppResult<HitokotoItem> {
+    let latest = history.last().map_or(0, |r| r.revision);
+    if revision > latest {
+        return Err(AppError::NotFound(format!(
+            "Hitokoto {} 不存在修订 {}",
+            current.uuid, revision
+        )));
+    }
+    let mut item = current.clone();
+    for later in history.iter().rev().take_while(|r| r.revision > revision) {
+        for change in later
+            .changes
+            .iter()
+            .filter(|change| REVERTIBLE_FIELDS.contains(&change.field.as_str()))
+        {
+            set_field_value(&mut item, &change.field, &change.old)?;
+        }
+    }
+    Ok(item)
+}
+
+impl Revision {
+    // 为一次修改创建修订，没有字段变化时返回 None
+    pub fn new(
+        history: &[Revision],
+        before: &HitokotoItem,
+        after: &HitokotoItem,
+        editor_id: u32,
+        editor: String,
+        action: RevisionAction,
+    ) -> Option<Self> {
+        let changes = diff_items(before, after);
+        if changes.is_empty() {
+            return None;
+        }
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        Some(Revision {
+            revision: history.last().map_or(1, |r| r.revision + 1),
+            hitokoto_uuid: after.uuid.clone(),
+            editor_id,
+            editor,
+            action,
+            reverted_to: None,
+            changes,
+            created_at,
+        })
+    }
+}
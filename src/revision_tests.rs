@@ -0,0 +1,78 @@
+use crate::AppError;
+use crate::item::ItemStatus;
+use crate::revision::{Revision, RevisionAction, diff_items, item_at_revision};
+use crate::tag::normalize_tags;
+use crate::test_support::sample_item;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revision_diff_and_revert() {
+        // 测试修订记录字段变化，并能按倒序还原到任意修订
+        let original = sample_item("第一版");
+        let mut history = Vec::new();
+
+        let mut second = original.clone();
+        second.hitokoto = "第二版内容".to_string();
+        second.length = 5;
+        second.from_who = Some("作者".to_string());
+        let revision = Revision::new(
+            &history,
+            &original,
+            &second,
+            1,
+            "tester".to_string(),
+            RevisionAction::Edit,
+        )
+        .unwrap();
+        let fields: Vec<&str> = revision.changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["hitokoto", "from_who"]);
+        history.push(revision);
+
+        let mut third = second.clone();
+        third.tags = normalize_tags(&["科幻".to_string()]);
+        history.push(
+            Revision::new(
+                &history,
+                &second,
+                &third,
+                1,
+                "tester".to_string(),
+                RevisionAction::Tags,
+            )
+            .unwrap(),
+        );
+        assert_eq!(history[1].revision, 2);
+        assert!(diff_items(&third, &third).is_empty());
+
+        let restored = item_at_revision(&third, &history, 1).unwrap();
+        assert!(diff_items(&restored, &second).is_empty());
+        let restored = item_at_revision(&third, &history, 0).unwrap();
+        assert!(diff_items(&restored, &original).is_empty());
+        assert_eq!(restored.length, 3);
+        assert!(matches!(
+            item_at_revision(&third, &history, 3),
+            Err(AppError::NotFound(_))
+        ));
+
+        // 回退不改变审核状态
+        let mut rejected = third.clone();
+        rejected.status = ItemStatus::Rejected;
+        history.push(
+            Revision::new(
+                &history,
+                &third,
+                &rejected,
+                1,
+                "tester".to_string(),
+                RevisionAction::Status,
+            )
+            .unwrap(),
+        );
+        let restored = item_at_revision(&rejected, &history, 0).unwrap();
+        assert_eq!(restored.status, ItemStatus::Rejected);
+        assert_eq!(restored.hitokoto, original.hitokoto);
+    }
+}
@@ -0,0 +1,301 @@
+use crate::item::HitokotoItem;
+use rocket::FromForm;
+use serde::Serialize;
+use std::collections::HashMap;
+
+// 各字段命中时的权重，来源和作者命中通常更能说明相关性
+const WEIGHT_HITOKOTO: f32 = 1.0;
+const WEIGHT_FROM: f32 = 1.5;
+const WEIGHT_FROM_WHO: f32 = 1.5;
+
+// 摘要中命中位置前后保留的字符数
+const SNIPPET_CONTEXT: usize = 30;
+
+// 搜索请求参数
+#[derive(FromForm)]
+pub struct SearchQuery {
+    pub q: String,
+    #[field(name = "type")]
+    pub item_type: Option<String>,
+    pub user_id: Option<u32>,
+    pub min_length: Option<u32>,
+    pub max_length: Option<u32>,
+    pub page: Option<usize>,     // 从 1 开始
+    pub per_page: Option<usize>, // 默认 10，最大 50
+}
+
+// 单条搜索结果
+#[derive(Serialize)]
+pub struct SearchHit {
+    pub item: HitokotoItem,
+    pub score: f32,
+    pub snippet: String, // 命中词以 <em> 标出的 Hitokoto 摘要，已做 HTML 转义
+}
+
+// 搜索结果分页
+#[derive(Serialize)]
+pub struct SearchResponse {
+    pub total: usize,
+    pub page: usize,
+    pub per_page: usize,
+    pub results: Vec<SearchHit>,
+}
+
+// 内存中的倒排索引
+// 中文按字切分为单字和相邻双字（n-gram），其他文字按单词切分并转为小写
+#[derive(Default)]
+pub struct SearchIndex {
+    postings: HashMap<String, HashMap<String, f32>>, // 词 -> (UUID -> 加权词频)
+    doc_terms: HashMap<String, Vec<String>>,         // UUID -> 该条目包含的词，用于删除
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 从全部数据重建索引
+    pub fn build(items: &[HitokotoItem]) -> Self {
+        let mut index = Self::new();
+        for item in items {
+            index.insert(item);
+        }
+        index
+    }
+
+    // 已索引的条目数
+    pub fn len(&self) -> usize {
+        self.doc_terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.doc_terms.is_empty()
+    }
+
+    // 添加或更新条目的索引
+    pub fn insert(&mut self, item: &HitokotoItem) {
+        self.remove(&item.uuid);
+
+        let mut weights: HashMap<String, f32> = HashMap::new();
+        let fields = [
+            (Some(item.hitokoto.as_str()), WEIGHT_HITOKOTO),
+            (Some(item.from.as_str()), WEIGHT_FROM),
+            (item.from_who.as_deref(), WEIGHT_FROM_WHO),
+        ];
+        for (text, weight) in fields {
+            for term in tokenize(text.unwrap_or_default()) {
+                *weights.entry(term).or_default() += weight;
+            }
+        }
+
+        let terms: Vec<String> = weights.keys().cloned().collect();
+        for (term, weight) in weights {
+            self.postings
+                .entry(term)
+                .or_default()
+                .insert(item.uuid.clone(), weight);
+        }
+        self.doc_terms.insert(item.uuid.clone(), terms);
+    }
+
+    // 从索引中移除条目
+    pub fn remove(&mut self, uuid: &str) {
+        if let Some(terms) = self.doc_terms.remove(uuid) {
+            for term in terms {
+                if let Some(docs) = self.postings.get_mut(&term) {
+                    docs.remove(uuid);
+                    if docs.is_empty() {
+                        self.postings.remove(&term);
+                    }
+                }
+            }
+        }
+    }
+
+    // 查询并按相关度降序返回 (UUID, 得分)
+    // 条目须包含查询中的全部词；得分为各词加权词频与逆文档频率之积的和
+    pub fn search(&self, query: &str) -> Vec<(String, f32)> {
+        let terms = query_terms(query);
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let total = self.len() as f32;
+        let mut scores: Option<HashMap<String, f32>> = None;
+        for term in &terms {
+            let Some(docs) = self.postings.get(term) else {
+                return Vec::new();
+            };
+            let idf = (1.0 + total / docs.len() as f32).ln();
+            scores = Some(match scores {
+                None => docs
+                    .iter()
+                    .map(|(uuid, weight)| (uuid.clone(), weight * idf))
+                    .collect(),
+                Some(previous) => previous
+                    .into_iter()
+                    .filter_map(|(uuid, score)| {
+                        docs.get(&uuid).map(|weight| (uuid, score + weight * idf))
+                    })
+                    .collect(),
+            });
+        }
+
+        let mut ranked: Vec<(String, f32)> = scores.unwrap_or_default().into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked
+    }
+}
+
+// 判断是否为中日韩文字
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}'   // 日文假名
+        | '\u{3400}'..='\u{4dbf}' // 扩展 A
+        | '\u{4e00}'..='\u{9fff}' // 基本汉字
+        | '\u{ac00}'..='\u{d7af}' // 韩文
+        | '\u{f900}'..='\u{faff}' // 兼容汉字
+    )
+}
+
+// 大小写折叠，索引、查询与高亮共用，部分字符折叠后不止一个字符
+fn fold(c: char) -> impl Iterator<Item = char> {
+    c.to_lowercase()
+}
+
+// 将文本切分为连续的中日韩文字段和单词段
+fn segments(text: &str) -> Vec<(bool, Vec<char>)> {
+    let mut result: Vec<(bool, Vec<char>)> = Vec::new();
+    for c in text.chars().flat_map(fold) {
+        let kind = if is_cjk(c) {
+            Some(true)
+        } else if c.is_alphanumeric() {
+            Some(false)
+        } else {
+            None
+        };
+        match (kind, result.last_mut()) {
+            (Some(cjk), Some((last_cjk, chars))) if *last_cjk == cjk && !chars.is_empty() => {
+                chars.push(c)
+            }
+            (Some(cjk), _) => result.push((cjk, vec![c])),
+            (None, Some((_, chars))) if !chars.is_empty() => result.push((false, Vec::new())),
+            (None, _) => {}
+        }
+    }
+    result.retain(|(_, chars)| !chars.is_empty());
+    result
+}
+
+// 索引用分词：中文输出单字和双字，其他文字输出整词
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for (cjk, chars) in segments(text) {
+        if cjk {
+            terms.extend(chars.iter().map(|c| c.to_string()));
+            terms.extend(chars.windows(2).map(|pair| pair.iter().collect()));
+        } else {
+            terms.push(chars.iter().collect());
+        }
+    }
+    terms
+}
+
+// 查询用分词：中文段长度大于 1 时只用双字，保证相邻字符一起出现
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for (cjk, chars) in segments(query) {
+        if cjk && chars.len() > 1 {
+            terms.extend(chars.windows(2).map(|pair| pair.iter().collect()));
+        } else {
+            terms.push(chars.iter().collect());
+        }
+    }
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+// 转义 HTML 特殊字符
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 生成带高亮的摘要：查询中的每个连续文字段命中处以 <em> 包裹
+// 文本较长时只保留首个命中位置附近的内容
+pub fn highlight(text: &str, query: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    // 折叠后的字符及其对应的原字符下标
+    let (lower, origin): (Vec<char>, Vec<usize>) = chars
+        .iter()
+        .enumerate()
+        .flat_map(|(i, c)| fold(*c).map(move |folded| (folded, i)))
+        .unzip();
+    let needles: Vec<Vec<char>> = segments(query).into_iter().map(|(_, s)| s).collect();
+
+    // 标记需要高亮的字符
+    let mut marked = vec![false; chars.len()];
+    for needle in &needles {
+        if needle.len() > lower.len() {
+            continue;
+        }
+        for start in 0..=lower.len() - needle.len() {
+            if lower[start..start + needle.len()] == needle[..] {
+                for &i in &origin[start..start + needle.len()] {
+                    marked[i] = true;
+                }
+            }
+        }
+    }
+
+    let (from, to) = match marked.iter().position(|m| *m) {
+        Some(first) if chars.len() > SNIPPET_CONTEXT * 2 => (
+            first.saturating_sub(SNIPPET_CONTEXT),
+            (first + SNIPPET_CONTEXT).min(chars.len()),
+        ),
+        _ => (0, chars.len().min(SNIPPET_CONTEXT * 2)),
+    };
+
+    let mut snippet = String::new();
+    if from > 0 {
+        snippet.push('…');
+    }
+    let mut in_mark = false;
+    for i in from..to {
+        if marked[i] != in_mark {
+            snippet.push_str(if marked[i] { "<em>" } else { "</em>" });
+            in_mark = marked[i];
+        }
+        snippet.push_str(&escape_html(&chars[i].to_string()));
+    }
+    if in_mark {
+        snippet.push_str("</em>");
+    }
+    if to < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+impl SearchQuery {
+    // 判断条目是否满足过滤条件
+    pub fn matches(&self, item: &HitokotoItem) -> bool {
+        self.item_type
+            .as_ref()
+            .is_none_or(|item_type| &item.item_type == item_type)
+            && self.user_id.is_none_or(|user_id| item.user_id == user_id)
+            && self.min_length.is_none_or(|min| item.length >= min)
+            && self.max_length.is_none_or(|max| item.length <= max)
+    }
+}
@@ -0,0 +1,64 @@
+use crate::HitokotoItem;
+use crate::search::{SearchIndex, highlight, query_terms, tokenize};
+use crate::test_support::sample_item;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(hitokoto: &str, from: &str, from_who: Option<&str>) -> HitokotoItem {
+        let mut item = sample_item(hitokoto);
+        item.from = from.to_string();
+        item.from_who = from_who.map(str::to_string);
+        item
+    }
+
+    #[test]
+    fn test_tokenize_cjk_and_latin() {
+        // 测试中文切分为单字和双字，英文按单词切分并转为小写
+        let terms = tokenize("三体 Hello");
+        assert!(terms.contains(&"三".to_string()));
+        assert!(terms.contains(&"三体".to_string()));
+        assert!(terms.contains(&"hello".to_string()));
+        assert_eq!(query_terms("黑暗森林"), vec!["暗森", "森林", "黑暗"]);
+    }
+
+    #[test]
+    fn test_search_ranking_and_removal() {
+        // 测试搜索结果须包含全部查询词、按相关度排序，删除后不再命中
+        let a = item("给岁月以文明，而不是给文明以岁月。", "三体", Some("刘慈欣"));
+        let b = item(
+            "弱小和无知不是生存的障碍，傲慢才是。",
+            "三体",
+            Some("刘慈欣"),
+        );
+        let c = item("岁月不居，时节如流。", "与曹公论盛孝章书", Some("孔融"));
+        let mut index = SearchIndex::build(&[a.clone(), b.clone(), c.clone()]);
+
+        let results = index.search("岁月");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, a.uuid); // 命中两次的排在前面
+
+        assert_eq!(index.search("三体 刘慈欣").len(), 2);
+        assert!(index.search("不存在的词").is_empty());
+
+        index.remove(&a.uuid);
+        let results = index.search("岁月");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, c.uuid);
+    }
+
+    #[test]
+    fn test_highlight_snippet() {
+        // 测试摘要高亮并转义HTML
+        assert_eq!(
+            highlight("岁月不居，<时节>如流。", "岁月"),
+            "<em>岁月</em>不居，&lt;时节&gt;如流。"
+        );
+        // 小写后不止一个字符的字母，高亮位置与原文对齐
+        assert_eq!(
+            highlight("İstanbul 的猫", "İSTANBUL"),
+            "<em>İstanbul</em> 的猫"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use crate::error::{AppError, AppResult};
+use crate::item::HitokotoItem;
+use aho_corasick::{AhoCorasick, MatchKind};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 命中敏感词时的处理方式
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SensitiveAction {
+    // 拒绝提交
+    Reject,
+    // 允许提交但需审核
+    Moderate,
+    // 允许提交，输出时以 * 替换
+    Mask,
+}
+
+// 词典中的一个敏感词
+#[derive(Clone, Debug)]
+pub struct SensitiveWord {
+    pub word: String,
+    pub action: SensitiveAction,
+}
+
+// 一次命中
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SensitiveMatch {
+    pub word: String,
+    pub action: SensitiveAction,
+    pub start: usize, // 字符位置（非字节）
+    pub end: usize,
+}
+
+// 测试文本的请求，user_id 为操作者
+#[derive(Deserialize)]
+pub struct SensitiveTestRequest {
+    pub user_id: u32,
+    pub text: String,
+}
+
+// 测试文本的结果
+#[derive(Serialize)]
+pub struct SensitiveTestResponse {
+    pub matches: Vec<SensitiveMatch>,
+    pub masked: String,
+    pub verdict: Option<SensitiveAction>, // 最严格的处理方式，未命中时为空
+}
+
+// 重新加载词典的请求
+#[derive(Deserialize)]
+pub struct SensitiveReloadRequest {
+    pub user_id: u32,
+}
+
+// 同一处理方式的敏感词及其自动机
+struct WordGroup {
+    action: SensitiveAction,
+    automaton: AhoCorasick,
+    words: Vec<String>,
+}
+
+// 敏感词过滤器，基于 Aho-Corasick 自动机一次扫描匹配敏感词
+// 每种处理方式单独构建自动机，避免较长的 mask 词遮住其中较短的 reject 词
+// 英文不区分大小写，同一处理方式内重叠时取最左最长的匹配
+#[derive(Default)]
+pub struct SensitiveFilter {
+    groups: Vec<WordGroup>,
+    len: usize,
+    loaded_at: u64, // 词典加载的时间，遮盖结果可能随之变化
+}
+
+impl SensitiveFilter {
+    pub fn new(words: Vec<SensitiveWord>) -> AppResult<Self> {
+        let len = words.len();
+        let mut groups = Vec::new();
+        for action in [
+            SensitiveAction::Reject,
+            SensitiveAction::Moderate,
+            SensitiveAction::Mask,
+        ] {
+            let words: Vec<String> = words
+                .iter()
+                .filter(|w| w.action == action)
+                .map(|w| w.word.clone())
+                .collect();
+            if words.is_empty() {
+                continue;
+            }
+            let automaton = AhoCorasick::builder()
+                .ascii_case_insensitive(true)
+                .match_kind(MatchKind::LeftmostLongest)
+                .build(&words)
+                .map_err(|e| AppError::Validation(format!("构建敏感词自动机失败: {}", e)))?;
+            groups.push(WordGroup {
+                action,
+                automaton,
+                words,
+            });
+        }
+        let loaded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        Ok(SensitiveFilter {
+            groups,
+            len,
+            loaded_at,
+        })
+    }
+
+    // 解析词典文件内容
+    // 每行一个词，可在空白后跟处理方式 reject / moderate / mask（默认 reject），# 开头为注释
+    pub fn parse(contents: &str) -> AppResult<Self> {
+        let mut words = Vec::new();
+        for (line_no, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let word = parts.next().unwrap_or_default().to_string();
+            let action = match parts.next() {
+                None | Some("reject") => SensitiveAction::Reject,
+                Some("moderate") => SensitiveAction::Moderate,
+                Some("mask") => SensitiveAction::Mask,
+                Some(other) => {
+                    return Err(AppError::Validation(format!(
+                        "敏感词词典第{}行的处理方式未知: {}",
+                        line_no + 1,
+                        other
+                    )));
+                }
+            };
+            words.push(SensitiveWord { word, action });
+        }
+        Self::new(words)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // 词典加载的时间，未加载词典时为 0
+    pub fn loaded_at(&self) -> u64 {
+        self.loaded_at
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // 查找文本中的全部敏感词，按出现位置排序
+    pub fn find(&self, text: &str) -> Vec<SensitiveMatch> {
+        let mut matches: Vec<SensitiveMatch> = self
+            .groups
+            .iter()
+            .flat_map(|group| {
+                group.automaton.find_iter(text).map(|m| {
+                    let start = text[..m.start()].chars().count();
+                    SensitiveMatch {
+                        word: group.words[m.pattern().as_usize()].clone(),
+                        action: group.action,
+                        start,
+                        end: start + text[m.start()..m.end()].chars().count(),
+                    }
+                })
+            })
+            .collect();
+        matches.sort_by_key(|m| (m.start, m.end));
+        matches
+    }
+
+    // 命中中最严格的处理方式：reject > moderate > mask
+    pub fn verdict(&self, text: &str) -> Option<SensitiveAction> {
+        self.groups
+            .iter()
+            .find(|group| group.automaton.is_match(text))
+            .map(|group| group.action)
+    }
+
+    // 将处理方式为 mask 的敏感词替换为等长的 *
+    pub fn mask(&self, text: &str) -> String {
+        let Some(group) = self
+            .groups
+            .iter()
+            .find(|group| group.action == SensitiveAction::Mask)
+        else {
+            return text.to_string();
+        };
+        let mut masked = String::with_capacity(text.len());
+        let mut last = 0;
+        for m in group.automaton.find_iter(text) {
+            masked.push_str(&text[last..m.start()]);
+            masked.push_str(&"*".repeat(text[m.start()..m.end()].chars().count()));
+            last = m.end();
+        }
+        masked.push_str(&text[last..]);
+        masked
+    }
+
+    // 对输出的条目进行屏蔽处理
+    pub fn mask_item(&self, mut item: HitokotoItem) -> HitokotoItem {
+        if self.is_empty() {
+            return item;
+        }
+        item.hitokoto = self.mask(&item.hitokoto);
+        item.from = self.mask(&item.from);
+        item.from_who = item.from_who.map(|who| self.mask(&who));
+        item
+    }
+}
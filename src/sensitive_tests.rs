@@ -0,0 +1,56 @@
+use crate::sensitive::{SensitiveAction, SensitiveFilter};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: &str = "# 测试词典\n坏词\nspam moderate\n笨蛋 mask\n笨蛋蛋 mask\n";
+
+    #[test]
+    fn test_parse_and_find() {
+        let filter = SensitiveFilter::parse(WORDS).unwrap();
+        assert_eq!(filter.len(), 4);
+
+        // 英文不区分大小写，位置按字符计算，重叠时取最长
+        let matches = filter.find("你这个笨蛋蛋，SPAM");
+        let found: Vec<(&str, usize, usize)> = matches
+            .iter()
+            .map(|m| (m.word.as_str(), m.start, m.end))
+            .collect();
+        assert_eq!(found, vec![("笨蛋蛋", 3, 6), ("spam", 7, 11)]);
+
+        assert!(SensitiveFilter::parse("坏词 delete").is_err());
+        assert!(SensitiveFilter::parse("").unwrap().find("坏词").is_empty());
+    }
+
+    #[test]
+    fn test_verdict_and_mask() {
+        let filter = SensitiveFilter::parse(WORDS).unwrap();
+        assert_eq!(filter.verdict("正常的句子"), None);
+        assert_eq!(filter.verdict("笨蛋"), Some(SensitiveAction::Mask));
+        assert_eq!(filter.verdict("笨蛋 spam"), Some(SensitiveAction::Moderate));
+        assert_eq!(filter.verdict("spam 坏词"), Some(SensitiveAction::Reject));
+
+        // 只屏蔽处理方式为 mask 的词
+        assert_eq!(filter.mask("笨蛋说了坏词"), "**说了坏词");
+    }
+
+    #[test]
+    fn test_longer_mask_word_does_not_hide_reject_word() {
+        // 较长的 mask 词包含 reject 词时，仍应按 reject 处理
+        let filter = SensitiveFilter::parse("坏词\n坏词语 mask\n").unwrap();
+        assert_eq!(filter.verdict("这个坏词语"), Some(SensitiveAction::Reject));
+        let matches = filter.find("这个坏词语");
+        let found: Vec<(&str, SensitiveAction)> = matches
+            .iter()
+            .map(|m| (m.word.as_str(), m.action))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("坏词", SensitiveAction::Reject),
+                ("坏词语", SensitiveAction::Mask)
+            ]
+        );
+    }
+}
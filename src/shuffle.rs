@@ -0,0 +1,130 @@
+use crate::daily::{mix, stable_hash};
+use crate::item::HitokotoItem;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+
+// 两次清除闲置状态之间的最短间隔（秒）
+const PRUNE_INTERVAL_SECS: u64 = 60;
+
+// 单个客户端的不重复随机状态
+// 排列由种子决定：条目按种子与 UUID 的哈希值排序，因此只需保存种子和已取到的位置
+// 候选条目变化时排列不变，消失的条目被跳过，新条目落在位置之后的本轮取到，之前的下一轮取到
+struct ClientShuffle {
+    seed: u64,
+    cursor: Option<(u64, String)>, // 本轮最后取到的位置，尚未取过时为空
+    filter: u64,                   // 筛选条件的哈希值，变化时重新开始
+    last_seen: u64,
+}
+
+// 按客户端令牌保存的不重复随机状态，闲置超过 ttl 秒的状态会被定期清除
+// 状态数达到 capacity 后不再为新客户端保存状态
+pub struct ClientShuffles {
+    clients: HashMap<String, ClientShuffle>,
+    ttl: u64,
+    capacity: usize,
+    last_prune: u64,
+}
+
+// 条目在种子决定的排列中的位置，哈希值相同时按 UUID 区分
+fn position(seed: u64, item: &HitokotoItem) -> (u64, &str) {
+    (
+        mix(stable_hash(item.uuid.bytes()) ^ seed),
+        item.uuid.as_str(),
+    )
+}
+
+impl ClientShuffles {
+    pub fn new(ttl: u64, capacity: usize) -> Self {
+        ClientShuffles {
+            clients: HashMap::new(),
+            ttl,
+            capacity,
+            last_prune: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    // 清除闲置超时的客户端状态
+    pub fn prune(&mut self, now: u64) {
+        let ttl = self.ttl;
+        self.clients
+            .retain(|_, client| now.saturating_sub(client.last_seen) <= ttl);
+        self.last_prune = now;
+    }
+
+    // 按客户端的随机排列取出下一条，一轮取完之前不会重复
+    // 取完一轮、筛选条件变化或状态过期时，用 rng 生成新种子重新打乱
+    // 状态数已达上限时新客户端退化为普通的均匀随机
+    pub fn next<'a>(
+        &mut self,
+        token: &str,
+        filter: &str,
+        candidates: &[&'a HitokotoItem],
+        now: u64,
+        rng: &mut impl Rng,
+    ) -> Option<&'a HitokotoItem> {
+        if candidates.is_empty() {
+            return None;
+        }
+        if now.saturating_sub(self.last_prune) >= PRUNE_INTERVAL_SECS {
+            self.prune(now);
+        }
+        if !self.clients.contains_key(token) && self.clients.len() >= self.capacity {
+            return candidates.choose(rng).copied();
+        }
+
+        let filter = stable_hash(filter.bytes());
+        let ttl = self.ttl;
+        let client = self
+            .clients
+            .entry(token.to_string())
+            .or_insert_with(|| ClientShuffle {
+                seed: rng.r#gen(),
+                cursor: None,
+                filter,
+                last_seen: now,
+            });
+        let expired = now.saturating_sub(client.last_seen) > ttl;
+        if expired || client.filter != filter {
+            client.seed = rng.r#gen();
+            client.cursor = None;
+            client.filter = filter;
+        }
+        client.last_seen = now;
+
+        let seed = client.seed;
+        let next = candidates
+            .iter()
+            .copied()
+            .filter(|&item| {
+                client
+                    .cursor
+                    .as_ref()
+                    .is_none_or(|(key, uuid)| position(seed, item) > (*key, uuid.as_str()))
+            })
+            .min_by_key(|&item| position(seed, item));
+        let item = match next {
+            Some(item) => item,
+            // 本轮已取完，换新种子开始下一轮
+            None => {
+                client.seed = rng.r#gen();
+                let seed = client.seed;
+                candidates
+                    .iter()
+                    .copied()
+                    .min_by_key(|&item| position(seed, item))?
+            }
+        };
+        let (key, uuid) = position(client.seed, item);
+        client.cursor = Some((key, uuid.to_string()));
+        Some(item)
+    }
+}
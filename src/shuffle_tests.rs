@@ -0,0 +1,91 @@
+use crate::HitokotoItem;
+use crate::shuffle::ClientShuffles;
+use crate::test_support::sample_items;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::collections::HashSet;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_shuffle_does_not_repeat() {
+        // 测试客户端在一轮内不会取到重复条目，闲置过期的状态会被清除
+        let items = sample_items(5);
+        let candidates: Vec<&HitokotoItem> = items.iter().collect();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut shuffles = ClientShuffles::new(60, 10);
+
+        let mut seen = HashSet::new();
+        for _ in 0..5 {
+            let item = shuffles.next("a", "", &candidates, 0, &mut rng).unwrap();
+            assert!(seen.insert(item.uuid.clone()));
+        }
+        // 取完一轮后重新开始
+        assert!(shuffles.next("a", "", &candidates, 10, &mut rng).is_some());
+        assert!(shuffles.next("b", "", &[], 10, &mut rng).is_none());
+
+        shuffles.next("b", "", &candidates, 20, &mut rng);
+        assert_eq!(shuffles.len(), 2);
+        shuffles.prune(75);
+        assert_eq!(shuffles.len(), 1);
+    }
+
+    #[test]
+    fn test_client_shuffle_survives_pool_changes() {
+        // 测试候选条目变化时本轮不重新开始：消失的条目被跳过，新条目在本轮或下一轮取到
+        let items = sample_items(10);
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut shuffles = ClientShuffles::new(60, 10);
+
+        let candidates: Vec<&HitokotoItem> = items[..6].iter().collect();
+        let mut seen = HashSet::new();
+        for _ in 0..3 {
+            let item = shuffles.next("a", "", &candidates, 0, &mut rng).unwrap();
+            seen.insert(item.uuid.clone());
+        }
+
+        // 去掉一个尚未取到的条目，并加入 4 个新条目
+        let removed = items[..6]
+            .iter()
+            .find(|item| !seen.contains(&item.uuid))
+            .unwrap();
+        let candidates: Vec<&HitokotoItem> = items
+            .iter()
+            .filter(|item| item.uuid != removed.uuid)
+            .collect();
+        let mut round = Vec::new();
+        loop {
+            let item = shuffles.next("a", "", &candidates, 0, &mut rng).unwrap();
+            if seen.contains(&item.uuid) {
+                break;
+            }
+            assert_ne!(item.uuid, removed.uuid);
+            round.push(item.uuid.clone());
+            seen.insert(item.uuid.clone());
+        }
+        // 原有的两个未取条目都在本轮取到，本轮没有重复
+        let original: HashSet<&String> = items[..6].iter().map(|item| &item.uuid).collect();
+        assert_eq!(round.iter().filter(|u| original.contains(u)).count(), 2);
+        assert_eq!(round.len(), round.iter().collect::<HashSet<_>>().len());
+    }
+
+    #[test]
+    fn test_client_shuffle_capacity() {
+        // 测试状态数达到上限后不再为新客户端保存状态，过期状态按间隔自动清除
+        let items = sample_items(3);
+        let candidates: Vec<&HitokotoItem> = items.iter().collect();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut shuffles = ClientShuffles::new(60, 2);
+
+        for token in ["a", "b", "c"] {
+            assert!(shuffles.next(token, "", &candidates, 0, &mut rng).is_some());
+        }
+        assert_eq!(shuffles.len(), 2);
+
+        shuffles.next("a", "", &candidates, 100, &mut rng);
+        shuffles.next("c", "", &candidates, 100, &mut rng);
+        assert_eq!(shuffles.len(), 2);
+    }
+}
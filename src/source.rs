@@ -0,0 +1,218 @@
+use crate::error::{AppError, AppResult};
+use crate::item::HitokotoItem;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+// 出处实体的种类：作品（对应 from）或作者（对应 from_who）
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SourceKind {
+    Work,
+    Author,
+}
+
+impl SourceKind {
+    // 用于提示信息的名称
+    pub fn label(self) -> &'static str {
+        match self {
+            SourceKind::Work => "作品",
+            SourceKind::Author => "作者",
+        }
+    }
+
+    // 持久化文件名
+    pub fn file_name(self) -> &'static str {
+        match self {
+            SourceKind::Work => "work.json",
+            SourceKind::Author => "author.json",
+        }
+    }
+
+    // 条目中对应的自由文本
+    pub fn item_text(self, item: &HitokotoItem) -> Option<&str> {
+        match self {
+            SourceKind::Work => Some(&item.from),
+            SourceKind::Author => item.from_who.as_deref(),
+        }
+    }
+
+    // 条目关联的该种实体ID
+    pub fn linked_id(self, item: &HitokotoItem) -> Option<&str> {
+        match self {
+            SourceKind::Work => item.work_id.as_deref(),
+            SourceKind::Author => item.author_id.as_deref(),
+        }
+    }
+
+    // 条目中指向该种实体的ID，用于修改关联
+    pub fn item_link(self, item: &mut HitokotoItem) -> &mut Option<String> {
+        match self {
+            SourceKind::Work => &mut item.work_id,
+            SourceKind::Author => &mut item.author_id,
+        }
+    }
+}
+
+// 作品或作者实体
+#[derive(Deserialize, Serialize, Clone)]
+pub struct SourceEntity {
+    pub id: String,
+    pub name: String, // 作品标题或作者姓名
+    #[serde(default)]
+    pub aliases: Vec<String>, // 其他写法，如译名、原名
+    pub created_at: u64,
+}
+
+pub type Work = SourceEntity;
+pub type Author = SourceEntity;
+
+// 作品或作者页面，附带关联的Hitokoto
+#[derive(Serialize)]
+pub struct SourceWithItems {
+    #[serde(flatten)]
+    pub entity: SourceEntity,
+    pub items: Vec<HitokotoItem>,
+}
+
+// 创建作品或作者的请求，user_id 为操作者
+#[derive(Deserialize)]
+pub struct NewSourceRequest {
+    pub user_id: u32,
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+// 合并重复实体的请求：将 source_id 合并到路径中的实体
+#[derive(Deserialize)]
+pub struct MergeSourceRequest {
+    pub user_id: u32,
+    pub source_id: String,
+}
+
+// 规范化名称用于匹配：去除书名号、引号等包裹符号，转为小写，连续空白合并为一个空格
+pub fn normalize_name(name: &str) -> String {
+    const WRAPPERS: &[char] = &[
+        '《', '》', '〈', '〉', '「', '」', '『', '』', '“', '”', '‘', '’', '"', '\'', '<', '>',
+    ];
+    name.trim()
+        .trim_matches(|c: char| WRAPPERS.contains(&c) || c.is_whitespace())
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl SourceEntity {
+    pub fn new(name: String, aliases: Vec<String>) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        SourceEntity {
+            id: Uuid::new_v4().to_string(),
+            name,
+            aliases,
+            created_at,
+        }
+    }
+
+    // 名称与全部别名规范化后的写法
+    pub fn normalized_names(&self) -> Vec<String> {
+        std::iter::once(&self.name)
+            .chain(&self.aliases)
+            .map(|name| normalize_name(name))
+            .filter(|name| !name.is_empty())
+            .collect()
+    }
+}
+
+// 作品或作者注册表，names 为规范化名称到实体ID的映射
+#[derive(Default)]
+pub struct SourceRegistry {
+    entities: HashMap<String, SourceEntity>,
+    names: HashMap<String, String>,
+}
+
+impl SourceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = &SourceEntity> {
+        self.entities.values()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&SourceEntity> {
+        self.entities.get(id)
+    }
+
+    // 根据自由文本查找实体ID
+    pub fn resolve(&self, text: &str) -> Option<&str> {
+        self.names.get(&normalize_name(text)).map(String::as_str)
+    }
+
+    // 添加实体，名称或别名已被其他实体使用时返回错误
+    pub fn insert(&mut self, kind: SourceKind, entity: SourceEntity) -> AppResult<()> {
+        let names = entity.normalized_names();
+        if normalize_name(&entity.name).is_empty() {
+            return Err(AppError::Validation(format!(
+                "{}名称不能为空",
+                kind.label()
+            )));
+        }
+        if let Some(existing) = names
+            .iter()
+            .find_map(|name| self.names.get(name).filter(|id| **id != entity.id))
+        {
+            return Err(AppError::AlreadyExists(format!(
+                "名称或别名已被{} {} 使用",
+                kind.label(),
+                existing
+            )));
+        }
+        for name in names {
+            self.names.insert(name, entity.id.clone());
+        }
+        self.entities.insert(entity.id.clone(), entity);
+        Ok(())
+    }
+
+    // 将 source_id 合并到 target_id：名称和别名并入目标，移除被合并的实体
+    pub fn merge(
+        &mut self,
+        kind: SourceKind,
+        target_id: &str,
+        source_id: &str,
+    ) -> AppResult<SourceEntity> {
+        if target_id == source_id {
+            return Err(AppError::Validation(format!(
+                "不能将{}合并到自身",
+                kind.label()
+            )));
+        }
+        let not_found = |id: &str| AppError::NotFound(format!("{}ID {} 不存在", kind.label(), id));
+        if !self.entities.contains_key(target_id) {
+            return Err(not_found(target_id));
+        }
+        let source = self
+            .entities
+            .remove(source_id)
+            .ok_or_else(|| not_found(source_id))?;
+
+        let target = self.entities.get_mut(target_id).unwrap();
+        let known = target.normalized_names();
+        for name in std::iter::once(source.name).chain(source.aliases) {
+            if !known.contains(&normalize_name(&name)) && !target.aliases.contains(&name) {
+                target.aliases.push(name);
+            }
+        }
+        for id in self.names.values_mut() {
+            if id == source_id {
+                *id = target_id.to_string();
+            }
+        }
+        Ok(target.clone())
+    }
+}
@@ -0,0 +1,44 @@
+use crate::AppError;
+use crate::source::{SourceEntity, SourceKind, SourceRegistry, normalize_name};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_registry_resolve_and_merge() {
+        // 测试作品名称规范化匹配、别名冲突与合并
+        assert_eq!(normalize_name(" 《三体》 "), "三体");
+        assert_eq!(
+            normalize_name("The  Three-Body Problem"),
+            "the three-body problem"
+        );
+
+        let mut works = SourceRegistry::new();
+        let santi = SourceEntity::new("三体".to_string(), Vec::new());
+        let english = SourceEntity::new(
+            "The Three-Body Problem".to_string(),
+            vec!["Three Body".to_string()],
+        );
+        let (santi_id, english_id) = (santi.id.clone(), english.id.clone());
+        works.insert(SourceKind::Work, santi).unwrap();
+        works.insert(SourceKind::Work, english).unwrap();
+        assert_eq!(works.resolve("《三体》"), Some(santi_id.as_str()));
+        assert_eq!(works.resolve("three body"), Some(english_id.as_str()));
+        assert!(matches!(
+            works.insert(
+                SourceKind::Work,
+                SourceEntity::new("「三体」".to_string(), Vec::new())
+            ),
+            Err(AppError::AlreadyExists(_))
+        ));
+
+        let merged = works
+            .merge(SourceKind::Work, &santi_id, &english_id)
+            .unwrap();
+        assert_eq!(merged.aliases, vec!["The Three-Body Problem", "Three Body"]);
+        assert_eq!(works.resolve("three body"), Some(santi_id.as_str()));
+        assert!(works.get(&english_id).is_none());
+        assert!(works.merge(SourceKind::Work, &santi_id, &santi_id).is_err());
+    }
+}
@@ -1,12 +1,50 @@
-use crate::collection::Collection;
-use crate::error::{AppError, AppResult};
-use crate::item::{HitokotoItem, RequestedHitokotoItem};
-use crate::user::{CollectionWithDetails, User, UserWithDetails};
-use rand::SeedableRng;
+use crate::category::{Category, CategoryRegistry, CategoryWithCount, FALLBACK_CATEGORY};
+use crate::collection::{
+    AddToCollectionResult, Collection, CollectionEntry, CollectionQuery, DeleteMode,
+    descendant_ids, would_create_cycle,
+};
+use crate::config::AppConfig;
+use crate::daily::{
+    DailyArchive, DailyArchiveEntry, DailyArchiveQuery, DailyPick, DailyPinRequest, DailyResponse,
+    date_for_timestamp, parse_date, pick_for_date,
+};
+use crate::dedup::{DuplicateIndex, NEAR_DUPLICATE_THRESHOLD};
+use crate::error::{AppError, AppResult, FieldError};
+use crate::events::{EventId, EventLog, EventQuery, EventSubscription, ItemEventKind};
+use crate::feed::{Feed, FeedScope};
+use crate::item::{
+    HitokotoItem, ItemPage, ItemStatus, LikeResponse, ListItemsQuery, MAX_CLIENT_TOKEN_LENGTH,
+    RandomItemQuery, RandomItemResponse, RequestedHitokotoItem, SortOrder, decode_cursor,
+    encode_cursor,
+};
+use crate::report::{
+    HandleReportRequest, MAX_REPORT_DETAIL_LENGTH, Report, ReportInboxQuery, ReportRequest,
+    ReportStatus, ReportTarget,
+};
+use crate::revision::{
+    EditHitokotoRequest, Revision, RevisionAction, SYSTEM_EDITOR, SYSTEM_EDITOR_ID, diff_items,
+    item_at_revision,
+};
+use crate::search::{SearchHit, SearchIndex, SearchQuery, SearchResponse, highlight};
+use crate::sensitive::{SensitiveAction, SensitiveFilter, SensitiveTestResponse};
+use crate::shuffle::ClientShuffles;
+use crate::source::{
+    MergeSourceRequest, NewSourceRequest, SourceEntity, SourceKind, SourceRegistry,
+    SourceWithItems, normalize_name,
+};
+use crate::strategy::SelectionContext;
+use crate::tag::{TagCount, TagIndex, normalize_tags};
+use crate::user::{CollectionWithDetails, User, UserRole, UserWithDetails};
+use crate::webhook::{
+    Delivery, DeliveryLogQuery, MAX_WEBHOOK_URL_LENGTH, NewWebhookRequest, Webhook,
+    WebhookDispatcher, WebhookEvent,
+};
 use rand::rngs::StdRng;
-use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use rocket::State;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::Mutex;
@@ -20,15 +58,59 @@ pub struct AppState {
     pub users: Mutex<HashMap<u32, User>>, // 用户存储，键为user_id
     pub collections: Mutex<HashMap<String, Collection>>, // 文集存储，键为collection_id
     pub rng: Mutex<StdRng>,
+    pub search_index: Mutex<SearchIndex>,    // Hitokoto全文索引
+    pub categories: Mutex<CategoryRegistry>, // 分类注册表
+    pub tag_index: Mutex<TagIndex>,          // 标签索引
+    pub dedup_index: Mutex<DuplicateIndex>,  // 重复检测索引
+    pub sensitive_filter: Mutex<SensitiveFilter>, // 敏感词过滤器，可在运行时重新加载
+    pub revisions: Mutex<HashMap<String, Vec<Revision>>>, // 修订历史，键为Hitokoto UUID
+    pub works: Mutex<SourceRegistry>,        // 作品注册表
+    pub authors: Mutex<SourceRegistry>,      // 作者注册表
+    pub reports: Mutex<HashMap<String, Report>>, // 举报，键为report_id
+    pub daily: Mutex<DailyArchive>,          // 每日一言的选取记录和预先指定
+    pub clients: Mutex<ClientShuffles>,      // 各客户端不重复随机的状态，仅保存在内存中
+    pub events: Mutex<EventLog>,             // 最近的Hitokoto事件及其广播，仅保存在内存中
+    pub webhooks: Arc<WebhookDispatcher>,    // 回调注册表与投递队列，由后台任务投递
+    pub config: AppConfig,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AppState {
     pub fn new() -> Self {
+        Self::with_config(AppConfig::default())
+    }
+
+    pub fn with_config(config: AppConfig) -> Self {
         Self {
             data: Mutex::new(Vec::new()),
             users: Mutex::new(HashMap::new()),
             collections: Mutex::new(HashMap::new()),
-            rng: Mutex::new(StdRng::from_entropy()),
+            rng: Mutex::new(match config.rng_seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            }),
+            search_index: Mutex::new(SearchIndex::new()),
+            categories: Mutex::new(CategoryRegistry::default()),
+            tag_index: Mutex::new(TagIndex::new()),
+            dedup_index: Mutex::new(DuplicateIndex::new()),
+            sensitive_filter: Mutex::new(SensitiveFilter::default()),
+            revisions: Mutex::new(HashMap::new()),
+            works: Mutex::new(SourceRegistry::new()),
+            authors: Mutex::new(SourceRegistry::new()),
+            reports: Mutex::new(HashMap::new()),
+            daily: Mutex::new(DailyArchive::default()),
+            clients: Mutex::new(ClientShuffles::new(
+                config.client_state_ttl_secs,
+                config.client_state_max,
+            )),
+            events: Mutex::new(EventLog::new(config.event_backlog_size)),
+            webhooks: Arc::new(WebhookDispatcher::new(config.webhook.clone(), false)),
+            config,
         }
     }
 
@@ -41,6 +123,9 @@ impl AppState {
         let data: Data = serde_json::from_str(&contents)
             .map_err(|e| AppError::Json(format!("数据文件格式错误: {}", e)))?;
         let mut store = self.data.lock().await;
+        *self.search_index.lock().await = SearchIndex::build(&data);
+        *self.tag_index.lock().await = TagIndex::build(&data);
+        *self.dedup_index.lock().await = DuplicateIndex::build(&data);
         *store = data;
         Ok(())
     }
@@ -104,96 +189,1936 @@ impl AppState {
         Ok(())
     }
 
-    pub async fn save_collections_to_file(&self) -> AppResult<()> {
-        let collections = self.collections.lock().await;
-        let collections_vec: Vec<Collection> = collections.values().cloned().collect();
-        let json = serde_json::to_string_pretty(&collections_vec)
-            .map_err(|e| AppError::Json(format!("序列化文集数据失败: {}", e)))?;
-        let mut file = File::create("collection.json").await
-            .map_err(|e| AppError::Io(format!("创建文集数据文件失败: {}", e)))?;
-        file.write_all(json.as_bytes()).await
-            .map_err(|e| AppError::Io(format!("写入文集数据失败: {}", e)))?;
-        file.flush().await
-            .map_err(|e| AppError::Io(format!("刷新文集数据文件失败: {}", e)))?;
-        Ok(())
+    // 从 category.json 加载分类注册表，文件不存在时使用默认分类
+    pub async fn load_categories_from_file(&self) -> AppResult<()> {
+        match File::open("category.json").await {
+            Ok(mut file) => {
+                let mut contents = String::new();
+                file.read_to_string(&mut contents)
+                    .await
+                    .map_err(|e| AppError::Io(format!("无法读取分类数据文件: {}", e)))?;
+                if !contents.trim().is_empty() {
+                    let categories: Vec<Category> = serde_json::from_str(&contents)
+                        .map_err(|e| AppError::Json(format!("分类数据文件格式错误: {}", e)))?;
+                    let registry = CategoryRegistry::new(categories);
+                    // 无法识别的旧分类会迁移到默认分类，自定义分类中必须包含它
+                    if registry.get(FALLBACK_CATEGORY).is_none() {
+                        return Err(AppError::Validation(format!(
+                            "分类数据文件中缺少默认分类 \"{}\"",
+                            FALLBACK_CATEGORY
+                        )));
+                    }
+                    *self.categories.lock().await = registry;
+                }
+            }
+            Err(_) => {
+                // 文件不存在，保持默认分类
+            }
+        }
+        Ok(())
+    }
+
+    // 从 revision.json 加载修订历史，文件不存在时为空
+    pub async fn load_revisions_from_file(&self) -> AppResult<()> {
+        match File::open("revision.json").await {
+            Ok(mut file) => {
+                let mut contents = String::new();
+                file.read_to_string(&mut contents)
+                    .await
+                    .map_err(|e| AppError::Io(format!("无法读取修订数据文件: {}", e)))?;
+                if !contents.trim().is_empty() {
+                    let revisions: Vec<Revision> = serde_json::from_str(&contents)
+                        .map_err(|e| AppError::Json(format!("修订数据文件格式错误: {}", e)))?;
+                    let mut revision_store = self.revisions.lock().await;
+                    for revision in revisions {
+                        revision_store
+                            .entry(revision.hitokoto_uuid.clone())
+                            .or_default()
+                            .push(revision);
+                    }
+                    for history in revision_store.values_mut() {
+                        history.sort_by_key(|r| r.revision);
+                    }
+                }
+            }
+            Err(_) => {
+                // 文件不存在，保持空的HashMap
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn save_revisions_to_file(&self) -> AppResult<()> {
+        let revisions = self.revisions.lock().await;
+        let revisions_vec: Vec<&Revision> = revisions.values().flatten().collect();
+        let json = serde_json::to_string_pretty(&revisions_vec)
+            .map_err(|e| AppError::Json(format!("序列化修订数据失败: {}", e)))?;
+        let mut file = File::create("revision.json")
+            .await
+            .map_err(|e| AppError::Io(format!("创建修订数据文件失败: {}", e)))?;
+        file.write_all(json.as_bytes())
+            .await
+            .map_err(|e| AppError::Io(format!("写入修订数据失败: {}", e)))?;
+        file.flush()
+            .await
+            .map_err(|e| AppError::Io(format!("刷新修订数据文件失败: {}", e)))?;
+        Ok(())
+    }
+
+    // 从 report.json 加载举报，文件不存在时为空
+    pub async fn load_reports_from_file(&self) -> AppResult<()> {
+        match File::open("report.json").await {
+            Ok(mut file) => {
+                let mut contents = String::new();
+                file.read_to_string(&mut contents)
+                    .await
+                    .map_err(|e| AppError::Io(format!("无法读取举报数据文件: {}", e)))?;
+                if !contents.trim().is_empty() {
+                    let reports: Vec<Report> = serde_json::from_str(&contents)
+                        .map_err(|e| AppError::Json(format!("举报数据文件格式错误: {}", e)))?;
+                    let mut report_store = self.reports.lock().await;
+                    for report in reports {
+                        report_store.insert(report.report_id.clone(), report);
+                    }
+                }
+            }
+            Err(_) => {
+                // 文件不存在，保持空的HashMap
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn save_reports_to_file(&self) -> AppResult<()> {
+        let reports = self.reports.lock().await;
+        let reports_vec: Vec<&Report> = reports.values().collect();
+        let json = serde_json::to_string_pretty(&reports_vec)
+            .map_err(|e| AppError::Json(format!("序列化举报数据失败: {}", e)))?;
+        let mut file = File::create("report.json")
+            .await
+            .map_err(|e| AppError::Io(format!("创建举报数据文件失败: {}", e)))?;
+        file.write_all(json.as_bytes())
+            .await
+            .map_err(|e| AppError::Io(format!("写入举报数据失败: {}", e)))?;
+        file.flush()
+            .await
+            .map_err(|e| AppError::Io(format!("刷新举报数据文件失败: {}", e)))?;
+        Ok(())
+    }
+
+    // 从 daily.json 加载每日一言记录，文件不存在时为空
+    pub async fn load_daily_from_file(&self) -> AppResult<()> {
+        match File::open("daily.json").await {
+            Ok(mut file) => {
+                let mut contents = String::new();
+                file.read_to_string(&mut contents)
+                    .await
+                    .map_err(|e| AppError::Io(format!("无法读取每日一言数据文件: {}", e)))?;
+                if !contents.trim().is_empty() {
+                    let archive: DailyArchive = serde_json::from_str(&contents)
+                        .map_err(|e| AppError::Json(format!("每日一言数据文件格式错误: {}", e)))?;
+                    *self.daily.lock().await = archive;
+                }
+            }
+            Err(_) => {
+                // 文件不存在，保持空的记录
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn save_daily_to_file(&self) -> AppResult<()> {
+        let daily = self.daily.lock().await;
+        let json = serde_json::to_string_pretty(&*daily)
+            .map_err(|e| AppError::Json(format!("序列化每日一言数据失败: {}", e)))?;
+        let mut file = File::create("daily.json")
+            .await
+            .map_err(|e| AppError::Io(format!("创建每日一言数据文件失败: {}", e)))?;
+        file.write_all(json.as_bytes())
+            .await
+            .map_err(|e| AppError::Io(format!("写入每日一言数据失败: {}", e)))?;
+        file.flush()
+            .await
+            .map_err(|e| AppError::Io(format!("刷新每日一言数据文件失败: {}", e)))?;
+        Ok(())
+    }
+
+    // 作品或作者对应的注册表
+    pub fn sources(&self, kind: SourceKind) -> &Mutex<SourceRegistry> {
+        match kind {
+            SourceKind::Work => &self.works,
+            SourceKind::Author => &self.authors,
+        }
+    }
+
+    // 从 work.json 或 author.json 加载作品或作者，文件不存在时为空
+    pub async fn load_sources_from_file(&self, kind: SourceKind) -> AppResult<()> {
+        match File::open(kind.file_name()).await {
+            Ok(mut file) => {
+                let mut contents = String::new();
+                file.read_to_string(&mut contents).await.map_err(|e| {
+                    AppError::Io(format!("无法读取{}数据文件: {}", kind.label(), e))
+                })?;
+                if !contents.trim().is_empty() {
+                    let entities: Vec<SourceEntity> =
+                        serde_json::from_str(&contents).map_err(|e| {
+                            AppError::Json(format!("{}数据文件格式错误: {}", kind.label(), e))
+                        })?;
+                    let mut registry = self.sources(kind).lock().await;
+                    for entity in entities {
+                        registry.insert(kind, entity)?;
+                    }
+                }
+            }
+            Err(_) => {
+                // 文件不存在，保持空的注册表
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn save_sources_to_file(&self, kind: SourceKind) -> AppResult<()> {
+        let registry = self.sources(kind).lock().await;
+        let entities: Vec<&SourceEntity> = registry.entities().collect();
+        let json = serde_json::to_string_pretty(&entities)
+            .map_err(|e| AppError::Json(format!("序列化{}数据失败: {}", kind.label(), e)))?;
+        let mut file = File::create(kind.file_name())
+            .await
+            .map_err(|e| AppError::Io(format!("创建{}数据文件失败: {}", kind.label(), e)))?;
+        file.write_all(json.as_bytes())
+            .await
+            .map_err(|e| AppError::Io(format!("写入{}数据失败: {}", kind.label(), e)))?;
+        file.flush()
+            .await
+            .map_err(|e| AppError::Io(format!("刷新{}数据文件失败: {}", kind.label(), e)))?;
+        Ok(())
+    }
+
+    // 从配置的词典文件加载敏感词，文件不存在时清空过滤器，返回词条数
+    pub async fn load_sensitive_words(&self) -> AppResult<usize> {
+        let path = &self.config.sensitive_words_path;
+        let filter = match File::open(path).await {
+            Ok(mut file) => {
+                let mut contents = String::new();
+                file.read_to_string(&mut contents)
+                    .await
+                    .map_err(|e| AppError::Io(format!("无法读取敏感词词典 {}: {}", path, e)))?;
+                SensitiveFilter::parse(&contents)?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                // 文件不存在，不过滤任何内容
+                SensitiveFilter::default()
+            }
+            Err(e) => {
+                return Err(AppError::Io(format!("无法打开敏感词词典 {}: {}", path, e)));
+            }
+        };
+        let count = filter.len();
+        *self.sensitive_filter.lock().await = filter;
+        Ok(count)
+    }
+
+    // 按用户的点赞记录重新统计各条目的点赞数，返回修改的条目数
+    // 用户和条目数据分别保存，以点赞记录为准修正两者不一致的情况
+    pub async fn recount_likes(&self) -> usize {
+        let users = self.users.lock().await;
+        let mut counts: HashMap<&str, u32> = HashMap::new();
+        for uuid in users.values().flat_map(|user| &user.likes) {
+            *counts.entry(uuid.as_str()).or_default() += 1;
+        }
+        let mut data = self.data.lock().await;
+        let mut recounted = 0;
+        for item in data.iter_mut() {
+            let count = counts.get(item.uuid.as_str()).copied().unwrap_or(0);
+            if item.like_count != count {
+                item.like_count = count;
+                recounted += 1;
+            }
+        }
+        recounted
+    }
+
+    // 将数据中无法识别的分类迁移为注册表中的分类，返回修改的条目数
+    pub async fn migrate_item_types(&self) -> usize {
+        let categories = self.categories.lock().await;
+        let mut data = self.data.lock().await;
+        let mut migrated = 0;
+        for item in data.iter_mut() {
+            if categories.get(&item.item_type).is_none() {
+                let code = categories.migrate(&item.item_type);
+                eprintln!("迁移分类: {} -> {} ({})", item.item_type, code, item.uuid);
+                item.item_type = code;
+                migrated += 1;
+            }
+        }
+        migrated
+    }
+
+    pub async fn save_collections_to_file(&self) -> AppResult<()> {
+        let collections = self.collections.lock().await;
+        let collections_vec: Vec<Collection> = collections.values().cloned().collect();
+        let json = serde_json::to_string_pretty(&collections_vec)
+            .map_err(|e| AppError::Json(format!("序列化文集数据失败: {}", e)))?;
+        let mut file = File::create("collection.json").await
+            .map_err(|e| AppError::Io(format!("创建文集数据文件失败: {}", e)))?;
+        file.write_all(json.as_bytes()).await
+            .map_err(|e| AppError::Io(format!("写入文集数据失败: {}", e)))?;
+        file.flush().await
+            .map_err(|e| AppError::Io(format!("刷新文集数据文件失败: {}", e)))?;
+        Ok(())
+    }
+}
+
+// 加载数据到内存 (用于启动时初始化)
+pub async fn load_data(config: AppConfig) -> AppResult<AppState> {
+    let mut state = AppState::with_config(config);
+    state.webhooks = Arc::new(WebhookDispatcher::new(state.config.webhook.clone(), true));
+    state.load_from_file().await?;
+    state.load_users_from_file().await?; // 也加载用户数据
+    state.load_collections_from_file().await?; // 也加载文集数据
+    state.load_categories_from_file().await?; // 也加载分类数据
+    state.load_sensitive_words().await?; // 也加载敏感词词典
+    state.load_revisions_from_file().await?; // 也加载修订历史
+    state.load_sources_from_file(SourceKind::Work).await?; // 也加载作品
+    state.load_sources_from_file(SourceKind::Author).await?; // 也加载作者
+    state.load_reports_from_file().await?; // 也加载举报
+    state.load_daily_from_file().await?; // 也加载每日一言记录
+    state.webhooks.load_from_files().await?; // 也加载回调和投递队列
+
+    // 迁移旧数据中不规范的分类、修正点赞数并保存
+    let migrated = state.migrate_item_types().await;
+    if migrated + state.recount_likes().await > 0 {
+        save_item(State::from(&state)).await?;
+    }
+    Ok(state)
+}
+
+// 辅助函数：验证用户是否存在并返回用户名
+async fn get_username_by_id(state: &State<AppState>, user_id: u32) -> AppResult<String> {
+    let users = state.users.lock().await;
+    let user = users
+        .get(&user_id)
+        .ok_or_else(|| AppError::NotFound(format!("用户ID {} 不存在，请先注册用户", user_id)))?;
+    Ok(user.username.clone())
+}
+
+// 辅助函数：验证操作者是资源的所有者或版主
+async fn check_owner_or_moderator(
+    state: &State<AppState>,
+    operator_id: u32,
+    owner_id: u32,
+) -> AppResult<()> {
+    let users = state.users.lock().await;
+    let operator = users.get(&operator_id).ok_or_else(|| {
+        AppError::NotFound(format!("用户ID {} 不存在，请先注册用户", operator_id))
+    })?;
+    if operator_id == owner_id || operator.is_moderator() {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            "只有所有者或版主可以执行此操作".to_string(),
+        ))
+    }
+}
+
+// 辅助函数：验证操作者是版主或管理员
+async fn check_moderator(state: &State<AppState>, operator_id: u32) -> AppResult<()> {
+    let users = state.users.lock().await;
+    let operator = users.get(&operator_id).ok_or_else(|| {
+        AppError::NotFound(format!("用户ID {} 不存在，请先注册用户", operator_id))
+    })?;
+    if operator.is_moderator() {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden("只有版主可以执行此操作".to_string()))
+    }
+}
+
+// 辅助函数：验证操作者是管理员
+async fn check_admin(state: &State<AppState>, operator_id: u32) -> AppResult<()> {
+    let users = state.users.lock().await;
+    let operator = users.get(&operator_id).ok_or_else(|| {
+        AppError::NotFound(format!("用户ID {} 不存在，请先注册用户", operator_id))
+    })?;
+    if operator.role == UserRole::Admin {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden("只有管理员可以执行此操作".to_string()))
+    }
+}
+
+// 辅助函数：验证用户存在并执行操作
+async fn with_user_mut<F, R>(
+    state: &State<AppState>,
+    user_id: u32,
+    operation: F,
+) -> AppResult<R>
+where
+    F: FnOnce(&mut User) -> R,
+{
+    let mut users = state.users.lock().await;
+    let user = users
+        .get_mut(&user_id)
+        .ok_or_else(|| AppError::NotFound(format!("用户ID {} 不存在，请先注册用户", user_id)))?;
+    Ok(operation(user))
+}
+
+// 辅助函数：根据文集ID获取文集副本
+async fn get_collection_by_id(
+    state: &State<AppState>,
+    collection_id: &str,
+) -> AppResult<Collection> {
+    let collections = state.collections.lock().await;
+    collections
+        .get(collection_id)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound(format!("文集ID {} 不存在", collection_id)))
+}
+
+// 辅助函数：从数据中筛选出文集包含的Hitokoto
+// 智能文集按查询条件动态计算，普通文集按 hitokoto_ids 引用
+fn collection_members<'a>(collection: &Collection, data: &'a Data) -> Vec<&'a HitokotoItem> {
+    match &collection.query {
+        Some(query) => data.iter().filter(|item| query.matches(item)).collect(),
+        None => data
+            .iter()
+            .filter(|item| collection.contains_hitokoto(&item.uuid))
+            .collect(),
+    }
+}
+
+// 获取随机Hitokoto条目
+// 如果没有数据则返回None，指定的文集不存在时返回错误
+pub async fn get_random_item(
+    state: &State<AppState>,
+    query: &RandomItemQuery,
+) -> AppResult<Option<RandomItemResponse>> {
+    let items = select_random_items(state, query, 1).await?;
+    Ok(items.into_iter().next())
+}
+
+// 一次随机获取至多 count 条互不相同的Hitokoto，筛选条件与单条获取相同
+// count 受配置项 max_random_count 限制，单条获取不受此限制
+pub async fn get_random_items(
+    state: &State<AppState>,
+    query: &RandomItemQuery,
+    count: usize,
+) -> AppResult<Vec<RandomItemResponse>> {
+    let max_count = state.config.max_random_count;
+    if count == 0 || count > max_count {
+        return Err(AppError::Validation(format!(
+            "count 必须在 1 到 {} 之间",
+            max_count
+        )));
+    }
+    select_random_items(state, query, count).await
+}
+
+// 辅助函数：整批在同一次加锁中不放回地选取，候选不足时返回全部候选
+async fn select_random_items(
+    state: &State<AppState>,
+    query: &RandomItemQuery,
+    count: usize,
+) -> AppResult<Vec<RandomItemResponse>> {
+    let client = query.client.as_deref().filter(|token| !token.is_empty());
+    if client.is_some_and(|token| token.chars().count() > MAX_CLIENT_TOKEN_LENGTH) {
+        return Err(AppError::Validation(format!(
+            "客户端令牌不能超过{}个字符",
+            MAX_CLIENT_TOKEN_LENGTH
+        )));
+    }
+    // 客户端模式不使用种子，同时提供时无法确定调用方的意图
+    if client.is_some() && query.seed.is_some() {
+        return Err(AppError::Validation(
+            "client 和 seed 不能同时提供".to_string(),
+        ));
+    }
+
+    let collection = match &query.collection {
+        Some(collection_id) => {
+            let collection = get_collection_by_id(state, collection_id).await?;
+            // 因举报被隐藏的文集视为不存在
+            if collection.hidden {
+                return Err(AppError::NotFound(format!(
+                    "文集ID {} 不存在",
+                    collection_id
+                )));
+            }
+            Some(collection)
+        }
+        None => None,
+    };
+
+    // 通过标签索引确定候选范围
+    let tagged = if query.tag.is_empty() {
+        None
+    } else {
+        state.tag_index.lock().await.uuids_with_all(&query.tag)
+    };
+
+    let data = state.data.lock().await;
+    let candidates: Vec<&HitokotoItem> = match &collection {
+        Some(collection) => collection_members(collection, &data),
+        None => data.iter().collect(),
+    }
+    .into_iter()
+    .filter(|item| {
+        item.is_public()
+            && tagged
+                .as_ref()
+                .is_none_or(|uuids| uuids.contains(&item.uuid))
+    })
+    .collect();
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let mut rng = state.rng.lock().await;
+    let (items, seed) = match client {
+        // 提供客户端令牌时按其随机排列依次选取，忽略种子和选取策略
+        // 一批跨越两轮排列时跳过本批中已取到的条目
+        Some(token) => {
+            let filter = format!(
+                "{}|{}",
+                query.collection.as_deref().unwrap_or_default(),
+                query.tag.join(",")
+            );
+            let mut clients = state.clients.lock().await;
+            let target = count.min(candidates.len());
+            let mut items: Vec<&HitokotoItem> = Vec::with_capacity(target);
+            for _ in 0..candidates.len() + target {
+                if items.len() == target {
+                    break;
+                }
+                match clients.next(token, &filter, &candidates, now, &mut *rng) {
+                    Some(item) if !items.iter().any(|seen| seen.uuid == item.uuid) => {
+                        items.push(item)
+                    }
+                    Some(_) => {}
+                    None => break,
+                }
+            }
+            (items, None)
+        }
+        // 每次选取都使用独立的种子，未指定时由全局随机数生成器产生
+        // 相同的种子、策略、数量和候选条目总是得到相同的结果
+        None => {
+            let seed = query.seed.unwrap_or_else(|| rng.r#gen());
+            let strategy = query.strategy.unwrap_or(state.config.default_strategy);
+            let context = SelectionContext {
+                now,
+                recency_half_life_days: state.config.recency_half_life_days,
+            };
+            let items = strategy.choose_multiple(
+                &candidates,
+                count,
+                &context,
+                &mut StdRng::seed_from_u64(seed),
+            );
+            (items, Some(seed))
+        }
+    };
+    let items: Vec<HitokotoItem> = items.into_iter().cloned().collect();
+    drop(rng);
+    drop(data);
+
+    let filter = state.sensitive_filter.lock().await;
+    Ok(items
+        .into_iter()
+        .map(|item| RandomItemResponse {
+            item: filter.mask_item(item),
+            seed,
+        })
+        .collect())
+}
+
+// 辅助函数：校验提交或编辑后的内容、分类和敏感词，一次返回所有字段的问题
+// 成功时返回规范的分类代码，以及是否因敏感词需要审核
+async fn check_submission(
+    state: &State<AppState>,
+    item: &RequestedHitokotoItem,
+) -> AppResult<(String, bool)> {
+    let mut errors = item.validate(&state.config.validation);
+    let item_type = match state.categories.lock().await.validate(&item.item_type) {
+        Ok(code) => code,
+        Err(AppError::Validation(msg)) => {
+            errors.push(FieldError::new("type", msg));
+            String::new()
+        }
+        Err(e) => return Err(e),
+    };
+
+    // 敏感词检查：需拒绝的作为字段错误返回，需审核的由调用方标记为待审核
+    let mut needs_moderation = false;
+    {
+        let filter = state.sensitive_filter.lock().await;
+        let fields = [
+            ("hitokoto", Some(&item.hitokoto)),
+            ("from", Some(&item.from)),
+            ("from_who", item.from_who.as_ref()),
+        ];
+        for (field, value) in fields {
+            match value.and_then(|value| filter.verdict(value)) {
+                Some(SensitiveAction::Reject) => {
+                    errors.push(FieldError::new(field, "包含不允许的敏感词"))
+                }
+                Some(SensitiveAction::Moderate) => needs_moderation = true,
+                _ => {}
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok((item_type, needs_moderation))
+    } else {
+        Err(AppError::InvalidFields(errors))
+    }
+}
+
+// 辅助函数：根据UUID获取Hitokoto副本
+async fn get_item_by_uuid(state: &State<AppState>, uuid: &str) -> AppResult<HitokotoItem> {
+    let data = state.data.lock().await;
+    data.iter()
+        .find(|item| item.uuid == uuid)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound(format!("Hitokoto UUID {} 不存在", uuid)))
+}
+
+// 辅助函数：把修改后的条目写回数据，并同步全文、标签和重复检测索引
+// expected 为修改所基于的条目，其间内容、标签或状态已被其他操作修改时返回错误
+// 只写回可编辑的字段，点赞数等其他字段保持最新值；返回写回前后的条目
+async fn update_item(
+    state: &State<AppState>,
+    expected: &HitokotoItem,
+    after: &HitokotoItem,
+) -> AppResult<(HitokotoItem, HitokotoItem)> {
+    let mut data = state.data.lock().await;
+    let item = data
+        .iter_mut()
+        .find(|item| item.uuid == expected.uuid)
+        .ok_or_else(|| AppError::NotFound(format!("Hitokoto UUID {} 不存在", expected.uuid)))?;
+    if !diff_items(item, expected).is_empty() {
+        return Err(AppError::Hitokoto(format!(
+            "Hitokoto {} 已被其他操作修改，请重新获取后再试",
+            expected.uuid
+        )));
+    }
+
+    let before = item.clone();
+    item.hitokoto = after.hitokoto.clone();
+    item.length = after.length;
+    item.item_type = after.item_type.clone();
+    item.from = after.from.clone();
+    item.from_who = after.from_who.clone();
+    item.tags = after.tags.clone();
+    item.status = after.status;
+    item.similar_to = after.similar_to.clone();
+    item.work_id = after.work_id.clone();
+    item.author_id = after.author_id.clone();
+    if !diff_items(&before, item).is_empty() {
+        item.touch();
+    }
+
+    let mut search_index = state.search_index.lock().await;
+    search_index.remove(&before.uuid);
+    search_index.insert(item);
+    drop(search_index);
+    let mut tag_index = state.tag_index.lock().await;
+    tag_index.remove(&before.uuid, &before.tags);
+    tag_index.add(&item.uuid, &item.tags);
+    drop(tag_index);
+    state.dedup_index.lock().await.update(&before, item);
+
+    Ok((before, item.clone()))
+}
+
+// 辅助函数：按提交规则重新校验修改后的条目，规范分类、匹配来源，内容变化时重新查重（排除条目自身）
+// 近似重复或命中需审核的敏感词时，已通过的条目重新进入待审核状态
+async fn review_changes(
+    state: &State<AppState>,
+    before: &HitokotoItem,
+    after: &mut HitokotoItem,
+) -> AppResult<()> {
+    let requested = RequestedHitokotoItem {
+        hitokoto: after.hitokoto.clone(),
+        item_type: after.item_type.clone(),
+        from: after.from.clone(),
+        from_who: after.from_who.clone(),
+        user_id: after.user_id,
+        tags: Vec::new(),
+    };
+    let (item_type, mut needs_review) = check_submission(state, &requested).await?;
+    after.item_type = item_type;
+    after.length = after.hitokoto.chars().count() as u32;
+    (after.work_id, after.author_id) =
+        resolve_sources(state, &after.from, after.from_who.as_deref()).await;
+
+    if after.hitokoto != before.hitokoto {
+        let dedup_index = state.dedup_index.lock().await;
+        if let Some(existing) = dedup_index.find_exact(&after.hitokoto)
+            && existing != after.uuid
+        {
+            return Err(AppError::AlreadyExists(format!(
+                "内容与已有 Hitokoto 重复: {}",
+                existing
+            )));
+        }
+        let similar: Vec<String> = dedup_index
+            .find_similar(&after.hitokoto, NEAR_DUPLICATE_THRESHOLD)
+            .into_iter()
+            .filter(|similar| *similar != after.uuid)
+            .collect();
+        if !similar.is_empty() {
+            needs_review = true;
+            after.similar_to = similar;
+        }
+    }
+    if needs_review && after.status == ItemStatus::Approved {
+        after.status = ItemStatus::Pending;
+    }
+    Ok(())
+}
+
+// 辅助函数：记录一次修订并保存，没有字段变化时不记录
+async fn record_revision(
+    state: &State<AppState>,
+    before: &HitokotoItem,
+    after: &HitokotoItem,
+    editor_id: u32,
+    action: RevisionAction,
+    reverted_to: Option<u32>,
+) {
+    let editor = if editor_id == SYSTEM_EDITOR_ID {
+        SYSTEM_EDITOR.to_string()
+    } else {
+        match get_username_by_id(state, editor_id).await {
+            Ok(username) => username,
+            Err(_) => return,
+        }
+    };
+    let mut revisions = state.revisions.lock().await;
+    let history = revisions.entry(after.uuid.clone()).or_default();
+    let Some(mut revision) = Revision::new(history, before, after, editor_id, editor, action)
+    else {
+        return;
+    };
+    revision.reverted_to = reverted_to;
+    history.push(revision);
+    drop(revisions);
+
+    if let Err(e) = state.save_revisions_to_file().await {
+        eprintln!("保存修订数据到文件失败: {e}");
+    }
+}
+
+// 辅助函数：按修改前后的条目发布Hitokoto事件，before 为空表示新提交
+// 事件流中只有公开的条目附带内容，回调总是附带；内容按敏感词设置遮蔽
+async fn publish_item_event(
+    state: &State<AppState>,
+    before: Option<&HitokotoItem>,
+    after: &HitokotoItem,
+) {
+    let Some(kind) = ItemEventKind::classify(before, after) else {
+        return;
+    };
+    let masked = state.sensitive_filter.lock().await.mask_item(after.clone());
+    let webhook_event = match kind {
+        ItemEventKind::Created => Some(WebhookEvent::ItemCreated),
+        ItemEventKind::Approved => Some(WebhookEvent::ItemApproved),
+        ItemEventKind::Deleted => Some(WebhookEvent::ItemDeleted),
+        ItemEventKind::Edited => None,
+    };
+    if let Some(webhook_event) = webhook_event {
+        notify_webhooks(state, webhook_event, serde_json::json!({ "item": masked })).await;
+    }
+    let item = after.is_public().then_some(masked);
+    state.events.lock().await.publish(kind, after, item);
+}
+
+// 辅助函数：把事件交给订阅了它的回调，data 为请求正文中的 data 字段
+async fn notify_webhooks(state: &State<AppState>, event: WebhookEvent, data: serde_json::Value) {
+    state.webhooks.enqueue(event, data).await;
+}
+
+// 辅助函数：根据自由文本匹配已知的作品和作者，返回 (work_id, author_id)
+async fn resolve_sources(
+    state: &State<AppState>,
+    from: &str,
+    from_who: Option<&str>,
+) -> (Option<String>, Option<String>) {
+    let work_id = state.works.lock().await.resolve(from).map(str::to_string);
+    let author_id = match from_who {
+        Some(from_who) => state
+            .authors
+            .lock()
+            .await
+            .resolve(from_who)
+            .map(str::to_string),
+        None => None,
+    };
+    (work_id, author_id)
+}
+
+// 添加新Hitokoto条目到数据存储
+// 如果数据存储未初始化则返回错误
+pub async fn add_item(
+    state: &State<AppState>,
+    new_item: RequestedHitokotoItem,
+) -> AppResult<HitokotoItem> {
+    let (item_type, needs_moderation) = check_submission(state, &new_item).await?;
+
+    // 验证用户是否存在并获取用户名
+    let username = get_username_by_id(state, new_item.user_id).await?;
+    let (work_id, author_id) =
+        resolve_sources(state, &new_item.from, new_item.from_who.as_deref()).await;
+
+    let mut data = state.data.lock().await;
+
+    // 创建完整的item
+    let mut full_item = HitokotoItem::new(
+        new_item.hitokoto,
+        item_type,
+        new_item.from,
+        new_item.from_who,
+        username,
+        new_item.user_id,
+    );
+    full_item.tags = normalize_tags(&new_item.tags);
+    full_item.work_id = work_id;
+    full_item.author_id = author_id;
+
+    // 重复检测：规范化后完全相同的直接拒绝，近似重复的标记为待审核
+    let mut dedup_index = state.dedup_index.lock().await;
+    if let Some(existing) = dedup_index.find_exact(&full_item.hitokoto) {
+        return Err(AppError::AlreadyExists(format!(
+            "内容与已有 Hitokoto 重复: {}",
+            existing
+        )));
+    }
+    let similar = dedup_index.find_similar(&full_item.hitokoto, NEAR_DUPLICATE_THRESHOLD);
+    if !similar.is_empty() {
+        full_item.status = ItemStatus::Pending;
+        full_item.similar_to = similar;
+    }
+    dedup_index.insert(&full_item.uuid, &full_item.hitokoto);
+    drop(dedup_index);
+    if needs_moderation {
+        full_item.status = ItemStatus::Pending;
+    }
+
+    // 更新全文索引和标签索引
+    state.search_index.lock().await.insert(&full_item);
+    state
+        .tag_index
+        .lock()
+        .await
+        .add(&full_item.uuid, &full_item.tags);
+
+    // 复制一份用于返回
+    let result = full_item.clone();
+    // 添加到数据中（移动所有权）
+    data.push(full_item);
+    drop(data);
+
+    publish_item_event(state, None, &result).await;
+    Ok(result)
+}
+
+// 为Hitokoto添加或移除标签，仅限所有者或版主，返回更新后的条目
+pub async fn update_item_tags(
+    state: &State<AppState>,
+    uuid: &str,
+    operator_id: u32,
+    tags: &[String],
+    remove: bool,
+) -> AppResult<HitokotoItem> {
+    let tags = normalize_tags(tags);
+    if tags.is_empty() {
+        return Err(AppError::Validation("请至少提供一个有效标签".to_string()));
+    }
+
+    let owner_id = {
+        let data = state.data.lock().await;
+        data.iter()
+            .find(|item| item.uuid == uuid)
+            .map(|item| item.user_id)
+            .ok_or_else(|| AppError::NotFound(format!("Hitokoto UUID {} 不存在", uuid)))?
+    };
+    check_owner_or_moderator(state, operator_id, owner_id).await?;
+
+    let mut data = state.data.lock().await;
+    let item = data
+        .iter_mut()
+        .find(|item| item.uuid == uuid)
+        .ok_or_else(|| AppError::NotFound(format!("Hitokoto UUID {} 不存在", uuid)))?;
+    let before = item.clone();
+    let mut tag_index = state.tag_index.lock().await;
+    if remove {
+        item.tags.retain(|tag| !tags.contains(tag));
+        tag_index.remove(uuid, &tags);
+    } else {
+        tag_index.add(uuid, &tags);
+        item.tags.extend(tags);
+    }
+    if item.tags != before.tags {
+        item.touch();
+    }
+    let result = item.clone();
+    drop(tag_index);
+    drop(data);
+
+    if let Err(e) = save_item(state).await {
+        eprintln!("保存数据到文件失败: {e}");
+    }
+    record_revision(
+        state,
+        &before,
+        &result,
+        operator_id,
+        RevisionAction::Tags,
+        None,
+    )
+    .await;
+    publish_item_event(state, Some(&before), &result).await;
+
+    Ok(result)
+}
+
+// 点赞或取消点赞Hitokoto，重复操作不会改变计数
+pub async fn set_item_like(
+    state: &State<AppState>,
+    uuid: &str,
+    user_id: u32,
+    like: bool,
+) -> AppResult<LikeResponse> {
+    // 先锁用户再锁数据，点赞记录和计数在同一段锁内一起修改
+    let mut users = state.users.lock().await;
+    let mut data = state.data.lock().await;
+    let item = data
+        .iter_mut()
+        .find(|item| item.uuid == uuid)
+        .ok_or_else(|| AppError::NotFound(format!("Hitokoto UUID {} 不存在", uuid)))?;
+    let user = users
+        .get_mut(&user_id)
+        .ok_or_else(|| AppError::NotFound(format!("用户ID {} 不存在，请先注册用户", user_id)))?;
+    let liked = user.likes.iter().any(|liked| liked == uuid);
+    let changed = like != liked;
+    if like && !liked {
+        user.likes.push(uuid.to_string());
+        item.like_count += 1;
+    } else if !like && liked {
+        user.likes.retain(|liked| liked != uuid);
+        item.like_count = item.like_count.saturating_sub(1);
+    }
+    let like_count = item.like_count;
+    drop(data);
+    drop(users);
+
+    if changed {
+        if let Err(e) = state.save_users_to_file().await {
+            eprintln!("保存用户数据到文件失败: {e}");
+        }
+        if let Err(e) = save_item(state).await {
+            eprintln!("保存数据到文件失败: {e}");
+        }
+    }
+
+    Ok(LikeResponse {
+        liked: like,
+        like_count,
+    })
+}
+
+// 获取用户点赞的公开Hitokoto，最近点赞的在前
+pub async fn get_user_likes(state: &State<AppState>, user_id: u32) -> AppResult<Vec<HitokotoItem>> {
+    let likes = get_user_by_id(state, user_id)
+        .await
+        .map(|user| user.likes)
+        .ok_or_else(|| AppError::NotFound(format!("用户ID {} 不存在", user_id)))?;
+
+    let data = state.data.lock().await;
+    let items: HashMap<&str, &HitokotoItem> =
+        data.iter().map(|item| (item.uuid.as_str(), item)).collect();
+    let liked: Vec<HitokotoItem> = likes
+        .iter()
+        .rev()
+        .filter_map(|uuid| items.get(uuid.as_str()))
+        .filter(|item| item.is_public())
+        .map(|item| (*item).clone())
+        .collect();
+    drop(items);
+    drop(data);
+
+    let filter = state.sensitive_filter.lock().await;
+    Ok(liked
+        .into_iter()
+        .map(|item| filter.mask_item(item))
+        .collect())
+}
+
+// 获取订阅源的内容：范围内最新的已通过条目，按创建时间从新到旧，至多 feed_size 条
+// 用户不存在或文集不存在、被隐藏时返回错误
+pub async fn get_feed(state: &State<AppState>, scope: &FeedScope) -> AppResult<Feed> {
+    let base_url = state.config.public_url.trim_end_matches('/');
+    let (title, link, collection) = match scope {
+        FeedScope::All => (
+            "Hitokoto 最新条目".to_string(),
+            format!("{}/hitokoto", base_url),
+            None,
+        ),
+        FeedScope::User(user_id) => {
+            let user = get_user_by_id(state, *user_id)
+                .await
+                .ok_or_else(|| AppError::NotFound(format!("用户ID {} 不存在", user_id)))?;
+            (
+                format!("{} 提交的 Hitokoto", user.username),
+                format!("{}/user/{}", base_url, user_id),
+                None,
+            )
+        }
+        FeedScope::Collection(collection_id) => {
+            let collection = get_collection_by_id(state, collection_id).await?;
+            if collection.hidden {
+                return Err(AppError::NotFound(format!(
+                    "文集ID {} 不存在",
+                    collection_id
+                )));
+            }
+            (
+                format!("文集：{}", collection.title),
+                format!("{}/hitokoto?collection={}", base_url, collection_id),
+                Some(collection),
+            )
+        }
+    };
+
+    let data = state.data.lock().await;
+    // 最后修改时间取范围内全部条目（含未公开的）的最后修改时间，条目被拒绝或隐藏时同样会变化
+    // 智能文集的成员由查询条件决定，任何条目的修改都可能改变其内容
+    let updated = data
+        .iter()
+        .filter(|item| match (scope, &collection) {
+            (FeedScope::User(user_id), _) => item.user_id == *user_id,
+            (_, Some(collection)) if !collection.is_smart() => {
+                collection.contains_hitokoto(&item.uuid)
+            }
+            _ => true,
+        })
+        .map(HitokotoItem::modified_at)
+        .chain(collection.as_ref().map(|collection| collection.updated_at))
+        .max()
+        .unwrap_or(0);
+    let mut items: Vec<&HitokotoItem> = match &collection {
+        Some(collection) => collection_members(collection, &data),
+        None => data.iter().collect(),
+    }
+    .into_iter()
+    .filter(|item| {
+        item.is_public()
+            && match scope {
+                FeedScope::User(user_id) => item.user_id == *user_id,
+                _ => true,
+            }
+    })
+    .collect();
+    items.sort_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| a.uuid.cmp(&b.uuid))
+    });
+    items.truncate(state.config.feed_size);
+    let items: Vec<HitokotoItem> = items.into_iter().cloned().collect();
+    drop(data);
+
+    // 重新加载敏感词词典会改变遮盖结果，同样计入最后修改时间
+    let filter = state.sensitive_filter.lock().await;
+    let updated = updated.max(filter.loaded_at());
+    let items: Vec<HitokotoItem> = items
+        .into_iter()
+        .map(|item| filter.mask_item(item))
+        .collect();
+    Ok(Feed {
+        title,
+        link,
+        feed_url: format!("{}{}", base_url, scope.path()),
+        updated,
+        items,
+    })
+}
+
+// 订阅Hitokoto事件，提供 last_event_id 时同时返回积压中该事件之后的事件
+// 分类支持名称和别名，未知分类返回错误
+pub async fn subscribe_events(
+    state: &State<AppState>,
+    query: &EventQuery,
+    last_event_id: Option<EventId>,
+) -> AppResult<EventSubscription> {
+    let item_type = match &query.item_type {
+        Some(item_type) => Some(state.categories.lock().await.validate(item_type)?),
+        None => None,
+    };
+    Ok(state
+        .events
+        .lock()
+        .await
+        .subscribe(last_event_id, item_type))
+}
+
+// 获取待审核的Hitokoto列表，仅限版主
+pub async fn get_pending_items(
+    state: &State<AppState>,
+    operator_id: u32,
+) -> AppResult<Vec<HitokotoItem>> {
+    check_moderator(state, operator_id).await?;
+    let data = state.data.lock().await;
+    Ok(data
+        .iter()
+        .filter(|item| item.status == ItemStatus::Pending)
+        .cloned()
+        .collect())
+}
+
+// 设置Hitokoto的审核状态，仅限版主，返回更新后的条目
+pub async fn set_item_status(
+    state: &State<AppState>,
+    uuid: &str,
+    operator_id: u32,
+    status: ItemStatus,
+) -> AppResult<HitokotoItem> {
+    check_moderator(state, operator_id).await?;
+    apply_item_status(state, uuid, operator_id, status).await
+}
+
+// 辅助函数：修改Hitokoto的审核状态并记录修订，调用方负责权限检查
+async fn apply_item_status(
+    state: &State<AppState>,
+    uuid: &str,
+    editor_id: u32,
+    status: ItemStatus,
+) -> AppResult<HitokotoItem> {
+    let (item, _) = transition_item_status(state, uuid, editor_id, None, status).await?;
+    Ok(item)
+}
+
+// 辅助函数：修改Hitokoto的审核状态，expected 不为空时仅在当前状态与之相同时修改
+// 返回修改后的条目以及状态是否发生了变化
+async fn transition_item_status(
+    state: &State<AppState>,
+    uuid: &str,
+    editor_id: u32,
+    expected: Option<ItemStatus>,
+    status: ItemStatus,
+) -> AppResult<(HitokotoItem, bool)> {
+    let mut data = state.data.lock().await;
+    let item = data
+        .iter_mut()
+        .find(|item| item.uuid == uuid)
+        .ok_or_else(|| AppError::NotFound(format!("Hitokoto UUID {} 不存在", uuid)))?;
+    if expected.is_some_and(|expected| item.status != expected) || item.status == status {
+        return Ok((item.clone(), false));
+    }
+    let before = item.clone();
+    item.status = status;
+    item.touch();
+    let result = item.clone();
+    state.dedup_index.lock().await.update(&before, &result);
+    drop(data);
+
+    if let Err(e) = save_item(state).await {
+        eprintln!("保存数据到文件失败: {e}");
+    }
+    record_revision(
+        state,
+        &before,
+        &result,
+        editor_id,
+        RevisionAction::Status,
+        None,
+    )
+    .await;
+    publish_item_event(state, Some(&before), &result).await;
+
+    Ok((result, true))
+}
+
+// 举报Hitokoto或文集，同一举报者对同一对象只能有一条待处理的举报
+// 待处理举报数达到配置的阈值时自动隐藏对象，等待版主处理
+pub async fn report_target(
+    state: &State<AppState>,
+    target_type: ReportTarget,
+    target_id: &str,
+    request: ReportRequest,
+) -> AppResult<Report> {
+    get_username_by_id(state, request.user_id).await?;
+    if request
+        .detail
+        .as_ref()
+        .is_some_and(|detail| detail.chars().count() > MAX_REPORT_DETAIL_LENGTH)
+    {
+        return Err(AppError::Validation(format!(
+            "补充说明不能超过{}个字符",
+            MAX_REPORT_DETAIL_LENGTH
+        )));
+    }
+    match target_type {
+        ReportTarget::Hitokoto => {
+            get_item_by_uuid(state, target_id).await?;
+        }
+        ReportTarget::Collection => {
+            get_collection_by_id(state, target_id).await?;
+        }
+    }
+
+    let mut reports = state.reports.lock().await;
+    if reports
+        .values()
+        .any(|report| report.is_open_duplicate_of(target_type, target_id, request.user_id))
+    {
+        return Err(AppError::AlreadyExists(
+            "你已举报过该内容，请等待处理".to_string(),
+        ));
+    }
+    let mut report = Report::new(
+        target_type,
+        target_id.to_string(),
+        request.user_id,
+        request.reason,
+        request.detail,
+    );
+    let open = reports
+        .values()
+        .filter(|r| r.is_open_for(target_type, target_id))
+        .count()
+        + 1;
+    let threshold = state.config.report_hide_threshold;
+    reports.insert(report.report_id.clone(), report.clone());
+    drop(reports);
+
+    // 达到阈值后的每次举报都尝试隐藏，版主在举报处理前恢复的对象会被再次隐藏
+    // 只有确实隐藏了对象时才标记，驳回举报时据此恢复
+    if threshold > 0
+        && open >= threshold
+        && set_target_hidden(state, &report, SYSTEM_EDITOR_ID, true).await?
+    {
+        report.hid_target = true;
+        if let Some(stored) = state.reports.lock().await.get_mut(&report.report_id) {
+            stored.hid_target = true;
+        }
+    }
+    if let Err(e) = state.save_reports_to_file().await {
+        eprintln!("保存举报数据到文件失败: {e}");
+    }
+
+    Ok(report)
+}
+
+// 辅助函数：因举报隐藏或恢复对象，返回对象的状态是否发生了变化
+// Hitokoto 通过审核状态隐藏（已通过的改为待审核，恢复时改回），文集通过 hidden 标记
+async fn set_target_hidden(
+    state: &State<AppState>,
+    report: &Report,
+    operator_id: u32,
+    hidden: bool,
+) -> AppResult<bool> {
+    match report.target_type {
+        ReportTarget::Hitokoto => {
+            let (from, to) = if hidden {
+                (ItemStatus::Approved, ItemStatus::Pending)
+            } else {
+                (ItemStatus::Pending, ItemStatus::Approved)
+            };
+            let (_, changed) =
+                transition_item_status(state, &report.target_id, operator_id, Some(from), to)
+                    .await?;
+            Ok(changed)
+        }
+        ReportTarget::Collection => {
+            let mut collections = state.collections.lock().await;
+            let changed = match collections.get_mut(&report.target_id) {
+                Some(collection) if collection.hidden != hidden => {
+                    collection.hidden = hidden;
+                    collection.touch();
+                    true
+                }
+                _ => false,
+            };
+            drop(collections);
+            if changed && let Err(e) = state.save_collections_to_file().await {
+                eprintln!("保存文集数据到文件失败: {e}");
+            }
+            Ok(changed)
+        }
+    }
+}
+
+// 列出举报，仅限版主，默认只列出待处理的举报，按提交时间排列
+pub async fn get_reports(
+    state: &State<AppState>,
+    query: &ReportInboxQuery,
+) -> AppResult<Vec<Report>> {
+    check_moderator(state, query.user_id).await?;
+    let status = query.status.unwrap_or_default();
+    let reports = state.reports.lock().await;
+    let mut listed: Vec<Report> = reports
+        .values()
+        .filter(|report| report.status == status)
+        .cloned()
+        .collect();
+    listed.sort_by(|a, b| {
+        a.created_at
+            .cmp(&b.created_at)
+            .then_with(|| a.report_id.cmp(&b.report_id))
+    });
+    Ok(listed)
+}
+
+// 处理举报，仅限版主，同一对象的全部待处理举报一并处理
+// 举报成立时 Hitokoto 被拒绝、文集保持隐藏；不成立时恢复因举报被自动隐藏的对象
+pub async fn handle_report(
+    state: &State<AppState>,
+    report_id: &str,
+    request: HandleReportRequest,
+    upheld: bool,
+) -> AppResult<Report> {
+    check_moderator(state, request.user_id).await?;
+
+    let mut reports = state.reports.lock().await;
+    let report = reports
+        .get(report_id)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound(format!("举报ID {} 不存在", report_id)))?;
+    if report.status != ReportStatus::Open {
+        return Err(AppError::Validation("该举报已处理".to_string()));
+    }
+    let status = if upheld {
+        ReportStatus::Resolved
+    } else {
+        ReportStatus::Dismissed
+    };
+    let mut was_hidden = false;
+    for other in reports
+        .values_mut()
+        .filter(|r| r.is_open_for(report.target_type, &report.target_id))
+    {
+        other.status = status;
+        other.handled_by = Some(request.user_id);
+        was_hidden |= other.hid_target;
+    }
+    let result = reports[report_id].clone();
+    drop(reports);
+
+    if let Err(e) = state.save_reports_to_file().await {
+        eprintln!("保存举报数据到文件失败: {e}");
+    }
+    if upheld {
+        match report.target_type {
+            ReportTarget::Hitokoto => {
+                apply_item_status(
+                    state,
+                    &report.target_id,
+                    request.user_id,
+                    ItemStatus::Rejected,
+                )
+                .await?;
+            }
+            ReportTarget::Collection => {
+                set_target_hidden(state, &report, request.user_id, true).await?;
+            }
+        }
+    } else if was_hidden {
+        set_target_hidden(state, &report, request.user_id, false).await?;
+    }
+
+    Ok(result)
+}
+
+// 编辑Hitokoto，仅限所有者或版主，返回更新后的条目
+// 编辑后的内容按提交规则重新校验和查重，每次修改都会记录修订
+pub async fn edit_item(
+    state: &State<AppState>,
+    uuid: &str,
+    request: EditHitokotoRequest,
+) -> AppResult<HitokotoItem> {
+    let before = get_item_by_uuid(state, uuid).await?;
+    check_owner_or_moderator(state, request.user_id, before.user_id).await?;
+
+    let mut after = before.clone();
+    if let Some(hitokoto) = request.hitokoto {
+        after.hitokoto = hitokoto;
+    }
+    if let Some(item_type) = request.item_type {
+        after.item_type = item_type;
+    }
+    if let Some(from) = request.from {
+        after.from = from;
+    }
+    match request.from_who {
+        Some(from_who) if from_who.trim().is_empty() => after.from_who = None,
+        Some(from_who) => after.from_who = Some(from_who),
+        None => {}
+    }
+    review_changes(state, &before, &mut after).await?;
+
+    let (before, after) = update_item(state, &before, &after).await?;
+    if let Err(e) = save_item(state).await {
+        eprintln!("保存数据到文件失败: {e}");
+    }
+    record_revision(
+        state,
+        &before,
+        &after,
+        request.user_id,
+        RevisionAction::Edit,
+        None,
+    )
+    .await;
+    publish_item_event(state, Some(&before), &after).await;
+
+    Ok(after)
+}
+
+// 获取Hitokoto的修订历史，按修订号升序
+pub async fn get_item_history(state: &State<AppState>, uuid: &str) -> AppResult<Vec<Revision>> {
+    get_item_by_uuid(state, uuid).await?;
+    let revisions = state.revisions.lock().await;
+    Ok(revisions.get(uuid).cloned().unwrap_or_default())
+}
+
+// 将Hitokoto回退到指定修订之后的状态（0 为提交时的原始版本），仅限版主
+// 回退后的内容与编辑一样重新校验和查重，回退本身也记录为一次修订
+pub async fn revert_item(
+    state: &State<AppState>,
+    uuid: &str,
+    operator_id: u32,
+    revision: u32,
+) -> AppResult<HitokotoItem> {
+    check_moderator(state, operator_id).await?;
+    let before = get_item_by_uuid(state, uuid).await?;
+    let mut after = {
+        let revisions = state.revisions.lock().await;
+        let history = revisions.get(uuid).map(Vec::as_slice).unwrap_or_default();
+        item_at_revision(&before, history, revision)?
+    };
+    review_changes(state, &before, &mut after).await?;
+
+    let (before, after) = update_item(state, &before, &after).await?;
+    if let Err(e) = save_item(state).await {
+        eprintln!("保存数据到文件失败: {e}");
+    }
+    record_revision(
+        state,
+        &before,
+        &after,
+        operator_id,
+        RevisionAction::Revert,
+        Some(revision),
+    )
+    .await;
+    publish_item_event(state, Some(&before), &after).await;
+
+    Ok(after)
+}
+
+// 辅助函数：按配置的时区计算今天的日期
+fn today(state: &State<AppState>) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    date_for_timestamp(now, state.config.daily_utc_offset_minutes)
+}
+
+// 获取今天的每日一言，同一天内所有访问者得到相同的条目
+// 当天第一次访问时选取并记录：优先使用管理员指定的条目，否则按日期的哈希值从已通过的条目中选取
+// 已记录的条目被删除或隐藏时重新选取；没有可选条目时返回 None
+pub async fn get_daily_item(state: &State<AppState>) -> AppResult<Option<DailyResponse>> {
+    let date = today(state);
+    let mut daily = state.daily.lock().await;
+    let data = state.data.lock().await;
+    let public_item = |uuid: &str| {
+        data.iter()
+            .find(|item| item.uuid == uuid && item.is_public())
+    };
+
+    let recorded = daily
+        .picks
+        .get(&date)
+        .and_then(|pick| Some((public_item(&pick.uuid)?, pick.pinned)));
+    let (item, pinned, changed) = match recorded {
+        Some((item, pinned)) => (item, pinned, false),
+        None => {
+            let pinned = daily.pins.get(&date).and_then(|uuid| public_item(uuid));
+            let (item, pinned) = match pinned {
+                Some(item) => (item, true),
+                None => {
+                    let candidates: Vec<&HitokotoItem> =
+                        data.iter().filter(|item| item.is_public()).collect();
+                    match pick_for_date(&date, &candidates) {
+                        Some(item) => (item, false),
+                        None => return Ok(None),
+                    }
+                }
+            };
+            let picked_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            daily.picks.insert(
+                date.clone(),
+                DailyPick {
+                    date: date.clone(),
+                    uuid: item.uuid.clone(),
+                    pinned,
+                    picked_at,
+                },
+            );
+            (item, pinned, true)
+        }
+    };
+    let item = item.clone();
+    drop(data);
+    drop(daily);
+
+    if changed && let Err(e) = state.save_daily_to_file().await {
+        eprintln!("保存每日一言数据到文件失败: {e}");
+    }
+
+    let item = state.sensitive_filter.lock().await.mask_item(item);
+    Ok(Some(DailyResponse { date, pinned, item }))
+}
+
+// 预先指定某天的每日一言，仅限管理员，返回规范化后的日期
+// 只能指定今天或之后的日期；指定今天时立即替换今天已选取的条目
+pub async fn pin_daily_item(
+    state: &State<AppState>,
+    request: DailyPinRequest,
+) -> AppResult<String> {
+    check_admin(state, request.user_id).await?;
+    let date = parse_date(&request.date).ok_or_else(|| {
+        AppError::Validation(format!("日期格式无效: {}，应为 YYYY-MM-DD", request.date))
+    })?;
+    let today = today(state);
+    if date < today {
+        return Err(AppError::Validation(
+            "只能指定今天或之后的每日一言".to_string(),
+        ));
+    }
+    if !get_item_by_uuid(state, &request.uuid).await?.is_public() {
+        return Err(AppError::Validation(
+            "只能指定已通过审核的 Hitokoto".to_string(),
+        ));
+    }
+
+    let mut daily = state.daily.lock().await;
+    daily.pins.insert(date.clone(), request.uuid);
+    if date == today {
+        daily.picks.remove(&today);
+    }
+    drop(daily);
+
+    if let Err(e) = state.save_daily_to_file().await {
+        eprintln!("保存每日一言数据到文件失败: {e}");
+    }
+    Ok(date)
+}
+
+// 列出历史每日一言，最近的在前
+pub async fn get_daily_archive(
+    state: &State<AppState>,
+    query: &DailyArchiveQuery,
+) -> AppResult<Vec<DailyArchiveEntry>> {
+    let before = match &query.before {
+        Some(before) => Some(parse_date(before).ok_or_else(|| {
+            AppError::Validation(format!("日期格式无效: {}，应为 YYYY-MM-DD", before))
+        })?),
+        None => None,
+    };
+    let limit = query.limit.unwrap_or(30).clamp(1, 365);
+
+    let daily = state.daily.lock().await;
+    let data = state.data.lock().await;
+    let entries: Vec<DailyArchiveEntry> = daily
+        .picks
+        .values()
+        .rev()
+        .filter(|pick| before.as_ref().is_none_or(|before| pick.date < *before))
+        .take(limit)
+        .map(|pick| DailyArchiveEntry {
+            date: pick.date.clone(),
+            pinned: pick.pinned,
+            item: data
+                .iter()
+                .find(|item| item.uuid == pick.uuid && item.is_public())
+                .cloned(),
+        })
+        .collect();
+    drop(data);
+    drop(daily);
+
+    let filter = state.sensitive_filter.lock().await;
+    Ok(entries
+        .into_iter()
+        .map(|entry| DailyArchiveEntry {
+            item: entry.item.map(|item| filter.mask_item(item)),
+            ..entry
+        })
+        .collect())
+}
+
+// 按使用次数列出标签，可按前缀过滤，只统计公开的条目
+pub async fn get_tag_counts(
+    state: &State<AppState>,
+    prefix: Option<&str>,
+    limit: usize,
+) -> Vec<TagCount> {
+    let data = state.data.lock().await;
+    let public: HashSet<&str> = data
+        .iter()
+        .filter(|item| item.is_public())
+        .map(|item| item.uuid.as_str())
+        .collect();
+    state
+        .tag_index
+        .lock()
+        .await
+        .top(prefix, limit, |uuid| public.contains(uuid))
+}
+
+// 获取全部分类及各分类的条目数
+pub async fn get_categories_with_counts(state: &State<AppState>) -> Vec<CategoryWithCount> {
+    let categories = state.categories.lock().await;
+    let data = state.data.lock().await;
+    categories
+        .categories()
+        .iter()
+        .map(|category| CategoryWithCount {
+            category: category.clone(),
+            count: data
+                .iter()
+                .filter(|item| item.item_type == category.code)
+                .count(),
+        })
+        .collect()
+}
+
+// 分页浏览Hitokoto
+// 使用键集分页：游标记录上一页最后一条的排序键，新插入的数据不会导致重复或遗漏
+pub async fn list_items(state: &State<AppState>, query: &ListItemsQuery) -> AppResult<ItemPage> {
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let (sort, order) = (
+        query.sort.unwrap_or_default(),
+        query.order.unwrap_or_default(),
+    );
+    let descending = order == SortOrder::Desc;
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(|cursor| decode_cursor(cursor, sort, order))
+        .transpose()?;
+    let filter = query.filter();
+
+    let data = state.data.lock().await;
+    let mut matched: Vec<((u64, String), &HitokotoItem)> = data
+        .iter()
+        .filter(|item| item.is_public() && filter.matches(item))
+        .map(|item| (query.sort_key(item), item))
+        .filter(|(key, _)| match &cursor {
+            Some(cursor) if descending => key < cursor,
+            Some(cursor) => key > cursor,
+            None => true,
+        })
+        .collect();
+    matched.sort_by(|a, b| {
+        if descending {
+            b.0.cmp(&a.0)
+        } else {
+            a.0.cmp(&b.0)
+        }
+    });
+
+    let next_cursor = if matched.len() > limit {
+        Some(encode_cursor(sort, order, &matched[limit - 1].0))
+    } else {
+        None
+    };
+    let items: Vec<HitokotoItem> = matched
+        .into_iter()
+        .take(limit)
+        .map(|(_, item)| item.clone())
+        .collect();
+    drop(data);
+
+    let filter = state.sensitive_filter.lock().await;
+    let items = items
+        .into_iter()
+        .map(|item| filter.mask_item(item))
+        .collect();
+
+    Ok(ItemPage { items, next_cursor })
+}
+
+// 全文搜索Hitokoto，按相关度排序并分页
+pub async fn search_items(state: &State<AppState>, query: &SearchQuery) -> SearchResponse {
+    let per_page = query.per_page.unwrap_or(10).clamp(1, 50);
+    let page = query.page.unwrap_or(1).max(1);
+
+    let ranked = state.search_index.lock().await.search(&query.q);
+
+    let data = state.data.lock().await;
+    let items: HashMap<&str, &HitokotoItem> =
+        data.iter().map(|item| (item.uuid.as_str(), item)).collect();
+    let matched: Vec<(&HitokotoItem, f32)> = ranked
+        .iter()
+        .filter_map(|(uuid, score)| items.get(uuid.as_str()).map(|item| (*item, *score)))
+        .filter(|(item, _)| item.is_public() && query.matches(item))
+        .collect();
+
+    let total = matched.len();
+    let page_items: Vec<(HitokotoItem, f32)> = matched
+        .into_iter()
+        .skip((page - 1).saturating_mul(per_page))
+        .take(per_page)
+        .map(|(item, score)| (item.clone(), score))
+        .collect();
+    drop(data);
+
+    // 摘要基于屏蔽后的文本生成，避免敏感词通过摘要泄露
+    let filter = state.sensitive_filter.lock().await;
+    let results = page_items
+        .into_iter()
+        .map(|(item, score)| {
+            let item = filter.mask_item(item);
+            let snippet = highlight(&item.hitokoto, &query.q);
+            SearchHit {
+                item,
+                score,
+                snippet,
+            }
+        })
+        .collect();
+
+    SearchResponse {
+        total,
+        page,
+        per_page,
+        results,
+    }
+}
+
+// 创建作品或作者，仅限版主
+// 创建后将来源文本与其名称或别名相符、且尚未关联的条目关联到新实体
+pub async fn create_source(
+    state: &State<AppState>,
+    kind: SourceKind,
+    request: NewSourceRequest,
+) -> AppResult<SourceEntity> {
+    check_moderator(state, request.user_id).await?;
+
+    let name = request.name.trim().to_string();
+    let aliases = request
+        .aliases
+        .iter()
+        .map(|alias| alias.trim().to_string())
+        .filter(|alias| !alias.is_empty())
+        .collect();
+    let entity = SourceEntity::new(name, aliases);
+    state
+        .sources(kind)
+        .lock()
+        .await
+        .insert(kind, entity.clone())?;
+    if let Err(e) = state.save_sources_to_file(kind).await {
+        eprintln!("保存{}数据到文件失败: {e}", kind.label());
+    }
+
+    let names = entity.normalized_names();
+    let mut data = state.data.lock().await;
+    let mut linked = 0;
+    for item in data.iter_mut() {
+        let matches = kind
+            .item_text(item)
+            .is_some_and(|text| names.contains(&normalize_name(text)));
+        let link = kind.item_link(item);
+        if matches && link.is_none() {
+            *link = Some(entity.id.clone());
+            linked += 1;
+        }
+    }
+    drop(data);
+    if linked > 0
+        && let Err(e) = save_item(state).await
+    {
+        eprintln!("保存数据到文件失败: {e}");
     }
-}
 
-// 加载数据到内存 (用于启动时初始化)
-pub async fn load_data() -> AppResult<AppState> {
-    let state = AppState::new();
-    state.load_from_file().await?;
-    state.load_users_from_file().await?; // 也加载用户数据
-    state.load_collections_from_file().await?; // 也加载文集数据
-    Ok(state)
+    Ok(entity)
 }
 
-// 辅助函数：验证用户是否存在并返回用户名
-async fn get_username_by_id(state: &State<AppState>, user_id: u32) -> AppResult<String> {
-    let users = state.users.lock().await;
-    let user = users
-        .get(&user_id)
-        .ok_or_else(|| AppError::NotFound(format!("用户ID {} 不存在，请先注册用户", user_id)))?;
-    Ok(user.username.clone())
-}
+// 获取作品或作者及其关联的公开条目
+pub async fn get_source_with_items(
+    state: &State<AppState>,
+    kind: SourceKind,
+    id: &str,
+) -> AppResult<SourceWithItems> {
+    let entity = state
+        .sources(kind)
+        .lock()
+        .await
+        .get(id)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound(format!("{}ID {} 不存在", kind.label(), id)))?;
 
-// 辅助函数：检查Hitokoto是否存在
-async fn hitokoto_exists(state: &State<AppState>, uuid: &str) -> bool {
     let data = state.data.lock().await;
-    data.iter().any(|item| item.uuid == uuid)
+    let items: Vec<HitokotoItem> = data
+        .iter()
+        .filter(|item| item.is_public() && kind.linked_id(item) == Some(id))
+        .cloned()
+        .collect();
+    drop(data);
+
+    let filter = state.sensitive_filter.lock().await;
+    let items = items
+        .into_iter()
+        .map(|item| filter.mask_item(item))
+        .collect();
+
+    Ok(SourceWithItems { entity, items })
 }
 
-// 辅助函数：验证用户存在并执行操作
-async fn with_user_mut<F, R>(
+// 合并重复的作品或作者，仅限版主
+// 被合并实体的名称和别名并入目标，关联的条目改为指向目标
+pub async fn merge_sources(
     state: &State<AppState>,
-    user_id: u32,
-    operation: F,
-) -> AppResult<R>
-where
-    F: FnOnce(&mut User) -> R,
-{
-    let mut users = state.users.lock().await;
-    let user = users
-        .get_mut(&user_id)
-        .ok_or_else(|| AppError::NotFound(format!("用户ID {} 不存在，请先注册用户", user_id)))?;
-    Ok(operation(user))
+    kind: SourceKind,
+    target_id: &str,
+    request: MergeSourceRequest,
+) -> AppResult<SourceEntity> {
+    check_moderator(state, request.user_id).await?;
+
+    let merged = state
+        .sources(kind)
+        .lock()
+        .await
+        .merge(kind, target_id, &request.source_id)?;
+    if let Err(e) = state.save_sources_to_file(kind).await {
+        eprintln!("保存{}数据到文件失败: {e}", kind.label());
+    }
+
+    let mut data = state.data.lock().await;
+    for item in data.iter_mut() {
+        let link = kind.item_link(item);
+        if link.as_deref() == Some(request.source_id.as_str()) {
+            *link = Some(target_id.to_string());
+        }
+    }
+    drop(data);
+    if let Err(e) = save_item(state).await {
+        eprintln!("保存数据到文件失败: {e}");
+    }
+
+    Ok(merged)
 }
 
-// 获取随机Hitokoto条目
-// 如果没有数据则返回None
-pub async fn get_random_item(state: &State<AppState>) -> Option<HitokotoItem> {
-    let data = state.data.lock().await;
-    let mut rng = state.rng.lock().await;
-    data.choose(&mut *rng).cloned()
+// 重新加载敏感词词典（仅管理员），返回词条数
+pub async fn reload_sensitive_words(state: &State<AppState>, operator_id: u32) -> AppResult<usize> {
+    check_admin(state, operator_id).await?;
+    state.load_sensitive_words().await
 }
 
-// 添加新Hitokoto条目到数据存储
-// 如果数据存储未初始化则返回错误
-pub async fn add_item(
+// 用当前词典测试一段文本（仅管理员）
+pub async fn test_sensitive_text(
     state: &State<AppState>,
-    new_item: RequestedHitokotoItem,
-) -> AppResult<HitokotoItem> {
-    // 首先验证用户是否存在并获取用户名
-    let username = get_username_by_id(state, new_item.user_id).await?;
+    operator_id: u32,
+    text: &str,
+) -> AppResult<SensitiveTestResponse> {
+    check_admin(state, operator_id).await?;
+    let filter = state.sensitive_filter.lock().await;
+    Ok(SensitiveTestResponse {
+        matches: filter.find(text),
+        masked: filter.mask(text),
+        verdict: filter.verdict(text),
+    })
+}
 
-    let mut data = state.data.lock().await;
+// 注册回调（仅管理员），省略 secret 时随机生成，返回的回调中包含密钥
+pub async fn register_webhook(
+    state: &State<AppState>,
+    request: NewWebhookRequest,
+) -> AppResult<Webhook> {
+    check_admin(state, request.user_id).await?;
 
-    // 创建完整的item
-    let full_item = HitokotoItem::new(
-        new_item.hitokoto,
-        new_item.item_type,
-        new_item.from,
-        new_item.from_who,
-        username,
-        new_item.user_id,
-    );
+    let url = request.url.trim().to_string();
+    if url.chars().count() > MAX_WEBHOOK_URL_LENGTH {
+        return Err(AppError::Validation(format!(
+            "url 不能超过{}个字符",
+            MAX_WEBHOOK_URL_LENGTH
+        )));
+    }
+    match reqwest::Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+        _ => {
+            return Err(AppError::Validation(
+                "url 必须是 http 或 https 网址".to_string(),
+            ));
+        }
+    }
+    let mut events = Vec::new();
+    for event in request.events {
+        if !events.contains(&event) {
+            events.push(event);
+        }
+    }
+    if events.is_empty() {
+        return Err(AppError::Validation("请至少订阅一个事件".to_string()));
+    }
+    if request.secret.as_deref().is_some_and(str::is_empty) {
+        return Err(AppError::Validation("secret 不能为空".to_string()));
+    }
 
-    // 复制一份用于返回
-    let result = full_item.clone();
-    // 添加到数据中（移动所有权）
-    data.push(full_item);
+    let webhook = Webhook::new(url, events, request.secret, request.user_id);
+    state.webhooks.register(webhook.clone()).await;
+    Ok(webhook)
+}
 
-    Ok(result)
+// 列出全部回调（仅管理员），不返回密钥
+pub async fn list_webhooks(state: &State<AppState>, operator_id: u32) -> AppResult<Vec<Webhook>> {
+    check_admin(state, operator_id).await?;
+    Ok(state
+        .webhooks
+        .list()
+        .await
+        .into_iter()
+        .map(Webhook::redacted)
+        .collect())
+}
+
+// 删除回调（仅管理员），尚未投递的记录标记为失败
+pub async fn delete_webhook(
+    state: &State<AppState>,
+    webhook_id: &str,
+    operator_id: u32,
+) -> AppResult<Webhook> {
+    check_admin(state, operator_id).await?;
+    state
+        .webhooks
+        .remove(webhook_id)
+        .await
+        .map(Webhook::redacted)
+}
+
+// 获取回调的投递记录（仅管理员），最新的在前
+pub async fn get_webhook_deliveries(
+    state: &State<AppState>,
+    webhook_id: &str,
+    query: &DeliveryLogQuery,
+) -> AppResult<Vec<Delivery>> {
+    check_admin(state, query.user_id).await?;
+    if state.webhooks.get(webhook_id).await.is_none() {
+        return Err(AppError::NotFound(format!("回调ID {} 不存在", webhook_id)));
+    }
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    Ok(state.webhooks.deliveries(webhook_id, limit).await)
 }
 
 // 保存数据到文件
@@ -232,6 +2157,12 @@ pub async fn add_user(
         eprintln!("保存用户数据到文件失败: {e}");
     }
 
+    notify_webhooks(
+        state,
+        WebhookEvent::UserRegistered,
+        serde_json::json!({ "user": user }),
+    )
+    .await;
     Ok(user)
 }
 
@@ -260,8 +2191,16 @@ async fn get_collection_with_details(
 ) -> Option<CollectionWithDetails> {
     let collections = state.collections.lock().await;
     if let Some(collection) = collections.get(collection_id) {
-        // 获取文集中的Hitokoto内容
-        let collection_items = get_hitokoto_items_by_uuids(state, &collection.hitokoto_ids).await;
+        // 获取文集中的Hitokoto内容，智能文集按查询条件动态计算
+        let collection_items = if collection.is_smart() {
+            let data = state.data.lock().await;
+            collection_members(collection, &data)
+                .into_iter()
+                .cloned()
+                .collect()
+        } else {
+            get_hitokoto_items_by_uuids(state, &collection.uuids()).await
+        };
 
         Some(CollectionWithDetails {
             collection_id: collection.collection_id.clone(),
@@ -269,7 +2208,13 @@ async fn get_collection_with_details(
             description: collection.description.clone(),
             user_id: collection.user_id,
             hitokoto_items: collection_items,
+            entries: collection.hitokoto_ids.clone(),
             created_at: collection.created_at,
+            forked_from: collection.forked_from.clone(),
+            fork_count: collection.fork_count,
+            query: collection.query.clone(),
+            parent_id: collection.parent_id.clone(),
+            children: Vec::new(),
         })
     } else {
         None
@@ -306,20 +2251,68 @@ pub async fn get_user_with_details(
         user_id,
         username,
         items: user_items,
-        collections: user_collections,
+        collections: build_collection_tree(user_collections),
     })
 }
 
-// 创建新文集
+// 辅助函数：将扁平的文集列表组装为树形结构，返回顶层文集
+// 父文集不在列表中的文集也视为顶层文集
+fn build_collection_tree(flat: Vec<CollectionWithDetails>) -> Vec<CollectionWithDetails> {
+    let ids: Vec<String> = flat.iter().map(|c| c.collection_id.clone()).collect();
+    let mut roots = Vec::new();
+    let mut by_parent: HashMap<String, Vec<CollectionWithDetails>> = HashMap::new();
+    for collection in flat {
+        match &collection.parent_id {
+            Some(parent_id) if ids.contains(parent_id) => by_parent
+                .entry(parent_id.clone())
+                .or_default()
+                .push(collection),
+            _ => roots.push(collection),
+        }
+    }
+
+    fn attach(
+        node: &mut CollectionWithDetails,
+        by_parent: &mut HashMap<String, Vec<CollectionWithDetails>>,
+    ) {
+        if let Some(children) = by_parent.remove(&node.collection_id) {
+            for mut child in children {
+                attach(&mut child, by_parent);
+                node.children.push(child);
+            }
+        }
+    }
+
+    for root in &mut roots {
+        attach(root, &mut by_parent);
+    }
+    roots
+}
+
+// 创建新文集，提供查询条件时创建智能文集，提供父文集时创建为子文集
 pub async fn create_collection(
     state: &State<AppState>,
     user_id: u32,
     title: String,
     description: Option<String>,
+    query: Option<CollectionQuery>,
+    parent_id: Option<String>,
 ) -> AppResult<Collection> {
     // 创建文集
-    let collection = Collection::new(title, description, user_id)
-        .map_err(|e| AppError::Collection(e.to_string()))?;
+    let mut collection = match query {
+        Some(query) => Collection::new_smart(title, description, user_id, query)?,
+        None => Collection::new(title, description, user_id)
+            .map_err(|e| AppError::Collection(e.to_string()))?,
+    };
+
+    // 验证父文集存在且属于同一用户
+    if let Some(parent_id) = parent_id {
+        let parent = get_collection_by_id(state, &parent_id).await?;
+        if parent.user_id != user_id {
+            return Err(AppError::Collection("父文集不属于该用户".to_string()));
+        }
+        collection.parent_id = Some(parent_id);
+    }
     let collection_id = collection.collection_id.clone();
 
     // 验证用户是否存在并将文集ID添加到用户的collections列表
@@ -343,33 +2336,341 @@ pub async fn create_collection(
         eprintln!("保存文集数据到文件失败: {e}");
     }
 
+    notify_webhooks(
+        state,
+        WebhookEvent::CollectionCreated,
+        serde_json::json!({ "collection": collection }),
+    )
+    .await;
     Ok(collection)
 }
 
-// 向文集添加Hitokoto
+// 向文集批量添加Hitokoto，返回新增与因重复跳过的UUID
+// 仅限文集所有者或版主；先校验全部UUID，任一不存在时整体失败；已在文集中或请求中重复的UUID跳过
 pub async fn add_hitokoto_to_collection(
     state: &State<AppState>,
     collection_id: String,
-    hitokoto_uuid: String,
-) -> AppResult<()> {
-    // 验证Hitokoto是否存在
-    if !hitokoto_exists(state, &hitokoto_uuid).await {
-        return Err(AppError::NotFound(format!("Hitokoto UUID {} 不存在", hitokoto_uuid)));
+    operator_id: u32,
+    hitokoto_uuids: Vec<String>,
+) -> AppResult<AddToCollectionResult> {
+    if hitokoto_uuids.is_empty() {
+        return Err(AppError::Validation(
+            "请至少提供一个 Hitokoto UUID".to_string(),
+        ));
     }
 
-    // 添加到文集
+    let owner_id = get_collection_by_id(state, &collection_id).await?.user_id;
+    check_owner_or_moderator(state, operator_id, owner_id).await?;
+
     let mut collections = state.collections.lock().await;
     let collection = collections
         .get_mut(&collection_id)
         .ok_or_else(|| AppError::NotFound(format!("文集ID {} 不存在", collection_id)))?;
+    if collection.is_smart() {
+        return Err(AppError::Collection(
+            "智能文集的内容由查询条件决定，不能手动添加".to_string(),
+        ));
+    }
+
+    // 验证Hitokoto是否存在
+    let missing: Vec<String> = {
+        let data = state.data.lock().await;
+        hitokoto_uuids
+            .iter()
+            .filter(|uuid| !data.iter().any(|item| &item.uuid == *uuid))
+            .cloned()
+            .collect()
+    };
+    if !missing.is_empty() {
+        return Err(bulk_error(&missing, "不存在", &[], ""));
+    }
 
-    collection.add_hitokoto(hitokoto_uuid);
+    // 已在文集中的条目不重复添加，在结果中列出
+    let result = collection.partition_uuids(&hitokoto_uuids);
+    for uuid in &result.added {
+        collection.add_hitokoto(uuid.clone());
+    }
+    if !result.added.is_empty() {
+        collection.touch();
+    }
     drop(collections);
 
     // 保存文集数据到文件
+    if !result.added.is_empty()
+        && let Err(e) = state.save_collections_to_file().await
+    {
+        eprintln!("保存文集数据到文件失败: {e}");
+    }
+
+    Ok(result)
+}
+
+// 从文集批量移除Hitokoto，仅限文集所有者或版主
+// 任一UUID不在文集中或重复时整体失败
+pub async fn remove_hitokoto_from_collection(
+    state: &State<AppState>,
+    collection_id: String,
+    operator_id: u32,
+    hitokoto_uuids: Vec<String>,
+) -> AppResult<usize> {
+    if hitokoto_uuids.is_empty() {
+        return Err(AppError::Validation(
+            "请至少提供一个 Hitokoto UUID".to_string(),
+        ));
+    }
+
+    let owner_id = get_collection_by_id(state, &collection_id).await?.user_id;
+    check_owner_or_moderator(state, operator_id, owner_id).await?;
+
+    let mut collections = state.collections.lock().await;
+    let collection = collections
+        .get_mut(&collection_id)
+        .ok_or_else(|| AppError::NotFound(format!("文集ID {} 不存在", collection_id)))?;
+    if collection.is_smart() {
+        return Err(AppError::Collection(
+            "智能文集的内容由查询条件决定，不能手动移除".to_string(),
+        ));
+    }
+
+    let absent = collection.absent_uuids(&hitokoto_uuids);
+    if !absent.is_empty() {
+        return Err(bulk_error(&absent, "重复或不在文集中", &[], ""));
+    }
+
+    let count = hitokoto_uuids.len();
+    for uuid in &hitokoto_uuids {
+        collection.remove_hitokoto(uuid);
+    }
+    collection.touch();
+    drop(collections);
+
     if let Err(e) = state.save_collections_to_file().await {
         eprintln!("保存文集数据到文件失败: {e}");
     }
 
-    Ok(())
+    Ok(count)
+}
+
+// 修改文集中某条目的批注，仅限文集所有者或版主
+pub async fn annotate_collection_entry(
+    state: &State<AppState>,
+    collection_id: String,
+    operator_id: u32,
+    hitokoto_uuid: String,
+    note: Option<String>,
+    highlight: Option<String>,
+) -> AppResult<CollectionEntry> {
+    let owner_id = get_collection_by_id(state, &collection_id).await?.user_id;
+    check_owner_or_moderator(state, operator_id, owner_id).await?;
+
+    let mut collections = state.collections.lock().await;
+    let collection = collections
+        .get_mut(&collection_id)
+        .ok_or_else(|| AppError::NotFound(format!("文集ID {} 不存在", collection_id)))?;
+    let entry = collection.annotate(&hitokoto_uuid, note, highlight)?;
+    collection.touch();
+    drop(collections);
+
+    if let Err(e) = state.save_collections_to_file().await {
+        eprintln!("保存文集数据到文件失败: {e}");
+    }
+
+    Ok(entry)
+}
+
+// 辅助函数：生成批量操作校验失败的错误信息
+fn bulk_error(
+    first: &[String],
+    first_reason: &str,
+    second: &[String],
+    second_reason: &str,
+) -> AppError {
+    let mut parts = Vec::new();
+    if !first.is_empty() {
+        parts.push(format!("以下 UUID {}: {}", first_reason, first.join(", ")));
+    }
+    if !second.is_empty() {
+        parts.push(format!(
+            "以下 UUID {}: {}",
+            second_reason,
+            second.join(", ")
+        ));
+    }
+    AppError::Collection(parts.join("; "))
+}
+
+// 复制文集到指定用户名下
+pub async fn fork_collection(
+    state: &State<AppState>,
+    collection_id: String,
+    user_id: u32,
+) -> AppResult<Collection> {
+    // 验证用户是否存在
+    get_username_by_id(state, user_id).await?;
+
+    // 复制文集并增加来源文集的复制次数
+    let mut collections = state.collections.lock().await;
+    let source = collections
+        .get_mut(&collection_id)
+        .ok_or_else(|| AppError::NotFound(format!("文集ID {} 不存在", collection_id)))?;
+    let forked = source.fork(user_id)?;
+    source.fork_count += 1;
+    let forked_id = forked.collection_id.clone();
+    collections.insert(forked_id.clone(), forked.clone());
+    drop(collections);
+
+    // 将新文集ID添加到用户的collections列表
+    with_user_mut(state, user_id, |user| user.add_collection_id(forked_id)).await?;
+
+    if let Err(e) = state.save_users_to_file().await {
+        eprintln!("保存用户数据到文件失败: {e}");
+    }
+    if let Err(e) = state.save_collections_to_file().await {
+        eprintln!("保存文集数据到文件失败: {e}");
+    }
+
+    notify_webhooks(
+        state,
+        WebhookEvent::CollectionCreated,
+        serde_json::json!({ "collection": forked }),
+    )
+    .await;
+    Ok(forked)
+}
+
+// 将智能文集转换为静态文集，以当前查询结果作为内容，仅限文集所有者或版主
+pub async fn snapshot_collection(
+    state: &State<AppState>,
+    collection_id: String,
+    operator_id: u32,
+) -> AppResult<Collection> {
+    let owner_id = get_collection_by_id(state, &collection_id).await?.user_id;
+    check_owner_or_moderator(state, operator_id, owner_id).await?;
+
+    let mut collections = state.collections.lock().await;
+    let collection = collections
+        .get_mut(&collection_id)
+        .ok_or_else(|| AppError::NotFound(format!("文集ID {} 不存在", collection_id)))?;
+    if !collection.is_smart() {
+        return Err(AppError::Collection("该文集不是智能文集".to_string()));
+    }
+
+    let data = state.data.lock().await;
+    let uuids = collection_members(collection, &data)
+        .into_iter()
+        .map(|item| item.uuid.clone())
+        .collect();
+    drop(data);
+    collection.snapshot(uuids);
+    collection.touch();
+    let result = collection.clone();
+    drop(collections);
+
+    if let Err(e) = state.save_collections_to_file().await {
+        eprintln!("保存文集数据到文件失败: {e}");
+    }
+
+    Ok(result)
+}
+
+// 移动文集到新的父文集下，parent_id 为空时移动到顶层
+pub async fn move_collection(
+    state: &State<AppState>,
+    collection_id: String,
+    parent_id: Option<String>,
+    operator_id: u32,
+) -> AppResult<Collection> {
+    let owner_id = get_collection_by_id(state, &collection_id).await?.user_id;
+    check_owner_or_moderator(state, operator_id, owner_id).await?;
+
+    let mut collections = state.collections.lock().await;
+    let collection = collections
+        .get(&collection_id)
+        .ok_or_else(|| AppError::NotFound(format!("文集ID {} 不存在", collection_id)))?;
+
+    if let Some(parent_id) = &parent_id {
+        let parent = collections
+            .get(parent_id)
+            .ok_or_else(|| AppError::NotFound(format!("文集ID {} 不存在", parent_id)))?;
+        if parent.user_id != collection.user_id {
+            return Err(AppError::Collection("父文集不属于该用户".to_string()));
+        }
+        if would_create_cycle(&collections, &collection_id, parent_id) {
+            return Err(AppError::Collection(
+                "不能将文集移动到自身或其子文集之下".to_string(),
+            ));
+        }
+    }
+
+    let collection = collections
+        .get_mut(&collection_id)
+        .ok_or_else(|| AppError::NotFound(format!("文集ID {} 不存在", collection_id)))?;
+    collection.parent_id = parent_id;
+    let result = collection.clone();
+    drop(collections);
+
+    if let Err(e) = state.save_collections_to_file().await {
+        eprintln!("保存文集数据到文件失败: {e}");
+    }
+
+    Ok(result)
+}
+
+// 删除文集，根据删除方式处理子文集，返回被删除的文集ID列表
+// 子文集与父文集属于同一用户，只需验证操作者是该文集的所有者或版主
+pub async fn delete_collection(
+    state: &State<AppState>,
+    collection_id: String,
+    mode: DeleteMode,
+    operator_id: u32,
+) -> AppResult<Vec<String>> {
+    let owner_id = get_collection_by_id(state, &collection_id).await?.user_id;
+    check_owner_or_moderator(state, operator_id, owner_id).await?;
+
+    let mut collections = state.collections.lock().await;
+    if !collections.contains_key(&collection_id) {
+        return Err(AppError::NotFound(format!(
+            "文集ID {} 不存在",
+            collection_id
+        )));
+    }
+
+    let mut deleted_ids = vec![collection_id.clone()];
+    match mode {
+        DeleteMode::Cascade => deleted_ids.extend(descendant_ids(&collections, &collection_id)),
+        DeleteMode::Unparent => {
+            for child in collections
+                .values_mut()
+                .filter(|c| c.parent_id.as_deref() == Some(collection_id.as_str()))
+            {
+                child.parent_id = None;
+            }
+        }
+    }
+
+    let mut owners = Vec::new();
+    for id in &deleted_ids {
+        if let Some(collection) = collections.remove(id) {
+            owners.push((collection.user_id, collection.collection_id));
+        }
+    }
+    drop(collections);
+
+    // 从所属用户的collections列表中移除
+    let mut users = state.users.lock().await;
+    for (user_id, id) in owners {
+        if let Some(user) = users.get_mut(&user_id) {
+            user.collections.retain(|c| c != &id);
+        }
+    }
+    drop(users);
+
+    if let Err(e) = state.save_users_to_file().await {
+        eprintln!("保存用户数据到文件失败: {e}");
+    }
+    if let Err(e) = state.save_collections_to_file().await {
+        eprintln!("保存文集数据到文件失败: {e}");
+    }
+
+    Ok(deleted_ids)
 }
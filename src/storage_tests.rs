@@ -0,0 +1,216 @@
+use crate::collection::Collection;
+use crate::item::RandomItemQuery;
+use crate::storage::{
+    AppState, add_hitokoto_to_collection, annotate_collection_entry, get_random_item,
+    get_random_items, remove_hitokoto_from_collection, snapshot_collection,
+};
+use crate::test_support::sample_items;
+use crate::user::User;
+use crate::{AppConfig, AppError};
+use rocket::State;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seeded_state(rng_seed: u64) -> AppState {
+        let config = AppConfig {
+            rng_seed: Some(rng_seed),
+            ..AppConfig::default()
+        };
+        let state = AppState::with_config(config);
+        state.data.lock().await.extend(sample_items(50));
+        state
+    }
+
+    #[rocket::async_test]
+    async fn test_random_item_with_seed_is_reproducible() {
+        // 测试指定种子时结果与全局随机数生成器的状态无关
+        let state = seeded_state(1).await;
+        let query = RandomItemQuery {
+            seed: Some(42),
+            ..Default::default()
+        };
+        let first = get_random_item(State::from(&state), &query)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.seed, Some(42));
+        get_random_item(State::from(&state), &RandomItemQuery::default())
+            .await
+            .unwrap();
+        let again = get_random_item(State::from(&state), &query)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(again.item.uuid, first.item.uuid);
+
+        // 返回的种子可以复现未指定种子时的结果
+        let unseeded = get_random_item(State::from(&state), &RandomItemQuery::default())
+            .await
+            .unwrap()
+            .unwrap();
+        let replay = RandomItemQuery {
+            seed: unseeded.seed,
+            ..Default::default()
+        };
+        let replayed = get_random_item(State::from(&state), &replay)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(replayed.item.uuid, unseeded.item.uuid);
+    }
+
+    #[rocket::async_test]
+    async fn test_configured_rng_seed_is_deterministic() {
+        // 测试配置相同的全局种子时随机序列一致
+        let (a, b) = (seeded_state(7).await, seeded_state(7).await);
+        for _ in 0..5 {
+            let query = RandomItemQuery::default();
+            let from_a = get_random_item(State::from(&a), &query).await.unwrap();
+            let from_b = get_random_item(State::from(&b), &query).await.unwrap();
+            let picked = |r: crate::item::RandomItemResponse| (r.seed, r.item.hitokoto);
+            assert_eq!(from_a.map(picked), from_b.map(picked));
+        }
+    }
+
+    #[rocket::async_test]
+    async fn test_random_items_are_distinct() {
+        // 测试一次获取多条时条目互不相同，候选不足时返回全部候选，超过上限时报错
+        let state = seeded_state(3).await;
+        let query = RandomItemQuery::default();
+        let items = get_random_items(State::from(&state), &query, 20)
+            .await
+            .unwrap();
+        let uuids: std::collections::HashSet<_> =
+            items.iter().map(|item| item.item.uuid.clone()).collect();
+        assert_eq!(uuids.len(), 20);
+
+        state.data.lock().await.truncate(5);
+        let client = RandomItemQuery {
+            client: Some("carousel".to_string()),
+            ..Default::default()
+        };
+        for query in [&query, &client] {
+            let items = get_random_items(State::from(&state), query, 10)
+                .await
+                .unwrap();
+            let uuids: std::collections::HashSet<_> =
+                items.iter().map(|item| item.item.uuid.clone()).collect();
+            assert_eq!(uuids.len(), 5);
+        }
+
+        for count in [0, 21] {
+            assert!(matches!(
+                get_random_items(State::from(&state), &query, count).await,
+                Err(AppError::Validation(_))
+            ));
+        }
+    }
+
+    #[rocket::async_test]
+    async fn test_recount_likes_from_user_records() {
+        // 测试点赞数以用户的点赞记录为准重新统计
+        let state = seeded_state(5).await;
+        let (first, second) = {
+            let mut data = state.data.lock().await;
+            data[0].like_count = 7;
+            (data[0].uuid.clone(), data[1].uuid.clone())
+        };
+        for name in ["甲", "乙"] {
+            let mut user = User::new(name.to_string()).unwrap();
+            user.likes = vec![second.clone()];
+            state.users.lock().await.insert(user.user_id, user);
+        }
+
+        assert_eq!(state.recount_likes().await, 2);
+        let data = state.data.lock().await;
+        let count = |uuid: &str| {
+            data.iter()
+                .find(|item| item.uuid == uuid)
+                .unwrap()
+                .like_count
+        };
+        assert_eq!((count(&first), count(&second)), (0, 2));
+    }
+
+    #[rocket::async_test]
+    async fn test_client_and_seed_are_exclusive() {
+        // 测试同时提供客户端令牌和种子时报错
+        let state = seeded_state(9).await;
+        let query = RandomItemQuery {
+            client: Some("carousel".to_string()),
+            seed: Some(1),
+            ..Default::default()
+        };
+        assert!(matches!(
+            get_random_item(State::from(&state), &query).await,
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[rocket::async_test]
+    async fn test_count_limit_only_applies_to_batches() {
+        // 测试 max_random_count 为 0 时仍可单条获取，批量获取被拒绝
+        let mut state = seeded_state(11).await;
+        state.config.max_random_count = 0;
+        let query = RandomItemQuery::default();
+        assert!(
+            get_random_item(State::from(&state), &query)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(matches!(
+            get_random_items(State::from(&state), &query, 1).await,
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[rocket::async_test]
+    async fn test_collection_mutations_require_owner() {
+        // 测试非所有者不能修改他人的文集，且文集内容保持不变
+        let state = seeded_state(13).await;
+        let uuid = state.data.lock().await[0].uuid.clone();
+        let owner = User::new("甲".to_string()).unwrap();
+        let other = User::new("乙".to_string()).unwrap();
+        let (owner_id, other_id) = (owner.user_id, other.user_id);
+        state
+            .users
+            .lock()
+            .await
+            .extend([(owner_id, owner), (other_id, other)]);
+        let collection = Collection::new("文集".to_string(), None, owner_id).unwrap();
+        let id = collection.collection_id.clone();
+        state
+            .collections
+            .lock()
+            .await
+            .insert(id.clone(), collection);
+
+        let state = State::from(&state);
+        let forbidden = |result: Result<_, AppError>| matches!(result, Err(AppError::Forbidden(_)));
+        assert!(forbidden(
+            add_hitokoto_to_collection(state, id.clone(), other_id, vec![uuid.clone()])
+                .await
+                .map(|_| ())
+        ));
+        assert!(forbidden(
+            remove_hitokoto_from_collection(state, id.clone(), other_id, vec![uuid.clone()])
+                .await
+                .map(|_| ())
+        ));
+        assert!(forbidden(
+            annotate_collection_entry(state, id.clone(), other_id, uuid, None, None)
+                .await
+                .map(|_| ())
+        ));
+        assert!(forbidden(
+            snapshot_collection(state, id.clone(), other_id)
+                .await
+                .map(|_| ())
+        ));
+        let collections = state.collections.lock().await;
+        assert!(collections[&id].hitokoto_ids.is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::item::HitokotoItem;
+use rand::Rng;
+use rand::seq::{IteratorRandom, SliceRandom};
+use rocket::FromFormField;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+// 最新条目相对很旧条目被选中的额外倍数
+const RECENCY_BOOST: f64 = 4.0;
+
+// 随机选取策略，可在请求中通过 strategy 参数指定，默认值来自配置
+#[derive(FromFormField, Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectionStrategy {
+    // 在全部候选中均匀随机
+    #[default]
+    #[field(value = "uniform")]
+    #[field(value = "random")]
+    Uniform,
+    // 先均匀选取分类，再在该分类中均匀选取，避免条目多的分类占据大部分结果
+    #[field(value = "category")]
+    Category,
+    // 按条目的新旧加权，越新越容易被选中，权重随时间按半衰期衰减
+    #[field(value = "recent")]
+    Recent,
+    // 按点赞数加权，权重为点赞数加一，未被点赞的条目仍有机会被选中
+    #[field(value = "popular")]
+    Popular,
+}
+
+// 选取时需要的参数
+pub struct SelectionContext {
+    pub now: u64,                    // 当前时间戳
+    pub recency_half_life_days: f64, // recent 策略的半衰期
+}
+
+impl SelectionStrategy {
+    // 按策略从候选中选取一条，没有候选时返回 None
+    pub fn choose<'a>(
+        self,
+        candidates: &[&'a HitokotoItem],
+        context: &SelectionContext,
+        rng: &mut impl Rng,
+    ) -> Option<&'a HitokotoItem> {
+        match self {
+            SelectionStrategy::Uniform => candidates.choose(rng).copied(),
+            SelectionStrategy::Category => {
+                let mut by_category: BTreeMap<&str, Vec<&'a HitokotoItem>> = BTreeMap::new();
+                for item in candidates {
+                    by_category
+                        .entry(item.item_type.as_str())
+                        .or_default()
+                        .push(item);
+                }
+                let items = by_category.into_values().choose(rng)?;
+                items.choose(rng).copied()
+            }
+            SelectionStrategy::Recent => {
+                let half_life = context.recency_half_life_days.max(f64::MIN_POSITIVE) * 86_400.0;
+                candidates
+                    .choose_weighted(rng, |item| {
+                        let age = context.now.saturating_sub(item.created_at) as f64;
+                        1.0 + RECENCY_BOOST * 0.5f64.powf(age / half_life)
+                    })
+                    .ok()
+                    .copied()
+            }
+            SelectionStrategy::Popular => candidates
+                .choose_weighted(rng, |item| item.like_count as u64 + 1)
+                .ok()
+                .copied(),
+        }
+    }
+
+    // 按策略不放回地选取至多 count 条，每选出一条就从候选中移除后再选下一条
+    pub fn choose_multiple<'a>(
+        self,
+        candidates: &[&'a HitokotoItem],
+        count: usize,
+        context: &SelectionContext,
+        rng: &mut impl Rng,
+    ) -> Vec<&'a HitokotoItem> {
+        let mut pool = candidates.to_vec();
+        let mut chosen = Vec::with_capacity(count.min(pool.len()));
+        while chosen.len() < count {
+            let Some(item) = self.choose(&pool, context, rng) else {
+                break;
+            };
+            pool.retain(|other| !std::ptr::eq(*other, item));
+            chosen.push(item);
+        }
+        chosen
+    }
+}
@@ -0,0 +1,72 @@
+use crate::HitokotoItem;
+use crate::item::RandomItemQuery;
+use crate::strategy::{SelectionContext, SelectionStrategy};
+use crate::test_support::sample_items;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rocket::form::Form;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_strategies() {
+        // 测试分类均衡策略不受各分类条目数影响，以及加权策略偏向新条目和热门条目
+        let mut items = sample_items(10);
+        items[0].item_type = "b".to_string();
+        for (i, item) in items.iter_mut().enumerate() {
+            item.created_at = 0;
+            item.like_count = if i == 1 { 99 } else { 0 };
+        }
+        items[2].created_at = 1_000 * 86_400;
+        let candidates: Vec<&HitokotoItem> = items.iter().collect();
+        let context = SelectionContext {
+            now: 1_000 * 86_400,
+            recency_half_life_days: 30.0,
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+        let count = |strategy: SelectionStrategy, uuid: &str, rng: &mut StdRng| {
+            (0..1000)
+                .filter(|_| strategy.choose(&candidates, &context, rng).unwrap().uuid == uuid)
+                .count()
+        };
+
+        // 唯一的 b 类条目约占一半
+        let rare = count(SelectionStrategy::Category, &items[0].uuid, &mut rng);
+        assert!((400..600).contains(&rare), "{}", rare);
+        // 最新条目权重为 5，其余约为 1
+        let recent = count(SelectionStrategy::Recent, &items[2].uuid, &mut rng);
+        assert!((250..500).contains(&recent), "{}", recent);
+        // 点赞 99 次的条目权重为 100，其余为 1
+        let popular = count(SelectionStrategy::Popular, &items[1].uuid, &mut rng);
+        assert!(popular > 850, "{}", popular);
+
+        assert!(
+            SelectionStrategy::Category
+                .choose(&[], &context, &mut rng)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_strategy_query_accepts_sort_alias() {
+        // 测试旧参数名 sort 与 strategy 等效，旧的取值 random 对应 uniform
+        fn parse(query: &str) -> Result<Option<SelectionStrategy>, rocket::form::Errors<'_>> {
+            Form::<RandomItemQuery>::parse(query).map(|q| q.strategy)
+        }
+        assert_eq!(
+            parse("strategy=popular").unwrap(),
+            Some(SelectionStrategy::Popular)
+        );
+        assert_eq!(
+            parse("sort=popular").unwrap(),
+            Some(SelectionStrategy::Popular)
+        );
+        assert_eq!(
+            parse("sort=random").unwrap(),
+            Some(SelectionStrategy::Uniform)
+        );
+        assert_eq!(parse("").unwrap(), None);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::item::HitokotoItem;
+use rocket::FromForm;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+// 单个标签的最大长度（字符数）
+pub const MAX_TAG_LENGTH: usize = 32;
+
+// 添加或移除标签的请求，user_id 为操作者
+#[derive(Deserialize)]
+pub struct TagRequest {
+    pub user_id: u32,
+    pub tags: Vec<String>,
+}
+
+// 标签及其使用次数
+#[derive(Serialize, PartialEq, Debug)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+// 标签云与自动补全的查询参数
+#[derive(FromForm)]
+pub struct TagListQuery {
+    pub prefix: Option<String>,
+    pub limit: Option<usize>, // 默认 20，最大 100
+}
+
+// 规范化标签：去除首尾空白和开头的 #，转为小写，连续空白合并为一个空格
+// 规范化后为空或过长时返回 None
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+    let tag = tag.split_whitespace().collect::<Vec<_>>().join(" ");
+    if tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH {
+        None
+    } else {
+        Some(tag)
+    }
+}
+
+// 规范化一组标签并去重
+pub fn normalize_tags(tags: &[String]) -> BTreeSet<String> {
+    tags.iter().filter_map(|tag| normalize_tag(tag)).collect()
+}
+
+// 标签索引：标签 -> 带有该标签的 Hitokoto UUID
+#[derive(Default)]
+pub struct TagIndex {
+    tags: HashMap<String, HashSet<String>>,
+}
+
+impl TagIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 从全部数据重建索引
+    pub fn build(items: &[HitokotoItem]) -> Self {
+        let mut index = Self::new();
+        for item in items {
+            index.add(&item.uuid, &item.tags);
+        }
+        index
+    }
+
+    // 为条目添加标签索引
+    pub fn add<'a>(&mut self, uuid: &str, tags: impl IntoIterator<Item = &'a String>) {
+        for tag in tags {
+            self.tags
+                .entry(tag.clone())
+                .or_default()
+                .insert(uuid.to_string());
+        }
+    }
+
+    // 移除条目的标签索引
+    pub fn remove<'a>(&mut self, uuid: &str, tags: impl IntoIterator<Item = &'a String>) {
+        for tag in tags {
+            if let Some(uuids) = self.tags.get_mut(tag) {
+                uuids.remove(uuid);
+                if uuids.is_empty() {
+                    self.tags.remove(tag);
+                }
+            }
+        }
+    }
+
+    // 同时带有全部指定标签的条目 UUID
+    // 全部标签规范化后均为空时返回 None，表示不按标签过滤，与列表接口的处理一致
+    pub fn uuids_with_all(&self, tags: &[String]) -> Option<HashSet<String>> {
+        let mut tags = tags.iter().filter_map(|tag| normalize_tag(tag));
+        let first = tags.next()?;
+        let mut result = self.tags.get(&first).cloned().unwrap_or_default();
+        for tag in tags {
+            match self.tags.get(&tag) {
+                Some(uuids) => result.retain(|uuid| uuids.contains(uuid)),
+                None => return Some(HashSet::new()),
+            }
+        }
+        Some(result)
+    }
+
+    // 按使用次数降序列出标签，可按前缀过滤（用于标签云和自动补全）
+    // 只统计 counted 返回 true 的条目，未计入任何条目的标签不列出
+    pub fn top(
+        &self,
+        prefix: Option<&str>,
+        limit: usize,
+        counted: impl Fn(&str) -> bool,
+    ) -> Vec<TagCount> {
+        let prefix = prefix.and_then(normalize_tag);
+        let mut counts: Vec<TagCount> = self
+            .tags
+            .iter()
+            .filter(|(tag, _)| prefix.as_deref().is_none_or(|p| tag.starts_with(p)))
+            .map(|(tag, uuids)| TagCount {
+                tag: tag.clone(),
+                count: uuids.iter().filter(|uuid| counted(uuid)).count(),
+            })
+            .filter(|tag| tag.count > 0)
+            .collect();
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+        counts.truncate(limit);
+        counts
+    }
+}
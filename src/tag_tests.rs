@@ -0,0 +1,57 @@
+use crate::tag::{TagCount, TagIndex, normalize_tag, normalize_tags};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_normalization_and_index() {
+        // 测试标签规范化以及标签索引的查找与计数
+        assert_eq!(normalize_tag("  #Sci  Fi "), Some("sci fi".to_string()));
+        assert_eq!(normalize_tag("#"), None);
+        assert_eq!(normalize_tag(&"长".repeat(33)), None);
+
+        let tags = normalize_tags(&["科幻".to_string(), "SF".to_string(), "sf".to_string()]);
+        assert_eq!(tags.len(), 2);
+
+        let mut index = TagIndex::new();
+        index.add("uuid-1", &tags);
+        index.add("uuid-2", &normalize_tags(&["科幻".to_string()]));
+
+        assert_eq!(
+            index.uuids_with_all(&["科幻".to_string()]).map(|u| u.len()),
+            Some(2)
+        );
+        assert_eq!(
+            index
+                .uuids_with_all(&["科幻".to_string(), "SF".to_string()])
+                .map(|u| u.len()),
+            Some(1)
+        );
+        // 全部标签规范化后为空时不按标签过滤
+        assert_eq!(index.uuids_with_all(&["#".to_string()]), None);
+        assert_eq!(
+            index.top(Some("科"), 10, |_| true),
+            vec![TagCount {
+                tag: "科幻".to_string(),
+                count: 2
+            }]
+        );
+        // 只统计公开条目，没有公开条目的标签不出现在标签云中
+        assert_eq!(
+            index.top(None, 10, |uuid| uuid == "uuid-2"),
+            vec![TagCount {
+                tag: "科幻".to_string(),
+                count: 1
+            }]
+        );
+
+        index.remove("uuid-1", &tags);
+        assert!(
+            index
+                .uuids_with_all(&["sf".to_string()])
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
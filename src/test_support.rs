@@ -0,0 +1,20 @@
+use crate::HitokotoItem;
+
+// 测试用的条目，除内容外其余字段相同
+pub fn sample_item(text: impl Into<String>) -> HitokotoItem {
+    HitokotoItem::new(
+        text.into(),
+        "a".to_string(),
+        "来源".to_string(),
+        None,
+        "tester".to_string(),
+        1,
+    )
+}
+
+// 内容依次为“第0条”“第1条”……的测试条目
+pub fn sample_items(count: usize) -> Vec<HitokotoItem> {
+    (0..count)
+        .map(|i| sample_item(format!("第{}条", i)))
+        .collect()
+}
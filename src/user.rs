@@ -1,3 +1,4 @@
+use crate::collection::{CollectionEntry, CollectionQuery};
 use crate::error::AppError;
 use crate::item::HitokotoItem;
 use serde::{Deserialize, Serialize};
@@ -9,7 +10,7 @@ pub struct UserWithDetails {
     pub user_id: u32,
     pub username: String,
     pub items: Vec<HitokotoItem>, // 用户直接提交的Hitokoto
-    pub collections: Vec<CollectionWithDetails>, // 用户的文集及其内容
+    pub collections: Vec<CollectionWithDetails>, // 用户的顶层文集，子文集嵌套在 children 中
 }
 
 // 文集及其包含的Hitokoto完整内容
@@ -20,7 +21,13 @@ pub struct CollectionWithDetails {
     pub description: Option<String>,
     pub user_id: u32,
     pub hitokoto_items: Vec<HitokotoItem>, // 文集中的Hitokoto完整内容
+    pub entries: Vec<CollectionEntry>,     // 文集条目的批注，智能文集为空
     pub created_at: u64,
+    pub forked_from: Option<String>,
+    pub fork_count: u32,
+    pub query: Option<CollectionQuery>, // 智能文集的查询条件
+    pub parent_id: Option<String>,
+    pub children: Vec<CollectionWithDetails>, // 子文集
 }
 
 // 新用户注册请求
@@ -29,12 +36,26 @@ pub struct NewUserRequest {
     pub username: String,
 }
 
+// 用户角色，目前通过直接修改 user.json 设置
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    #[default]
+    User,
+    Moderator,
+    Admin,
+}
+
 #[derive(Deserialize, Serialize, Clone)]
 pub struct User {
     pub user_id: u32,
     pub username: String,
     pub items: Vec<String>,       // 存储 Hitokoto 的 UUID 引用
     pub collections: Vec<String>, // 存储文集的 ID 引用
+    #[serde(default)]
+    pub role: UserRole,
+    #[serde(default)]
+    pub likes: Vec<String>, // 点赞（收藏）的 Hitokoto UUID，按点赞时间排列
 }
 
 impl User {
@@ -49,9 +70,16 @@ impl User {
             username,
             items: Vec::new(),
             collections: Vec::new(),
+            role: UserRole::User,
+            likes: Vec::new(),
         })
     }
 
+    // 是否具有审核权限（版主或管理员）
+    pub fn is_moderator(&self) -> bool {
+        matches!(self.role, UserRole::Moderator | UserRole::Admin)
+    }
+
     // 添加 Hitokoto UUID 到用户的 items 列表
     pub fn add_hitokoto_uuid(&mut self, uuid: String) {
         self.items.push(uuid);
@@ -0,0 +1,536 @@
+use crate::config::WebhookConfig;
+use crate::error::{AppError, AppResult};
+use hmac::{Hmac, Mac};
+use rocket::FromForm;
+use rocket::futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{Mutex, Notify};
+use uuid::Uuid;
+
+// 回调网址的最大长度
+pub const MAX_WEBHOOK_URL_LENGTH: usize = 2048;
+// 投递记录中保存的错误信息最大长度（字符数）
+const MAX_ERROR_LENGTH: usize = 200;
+// 没有待投递的记录时，投递任务最长的休眠时间（秒）
+const MAX_IDLE_SECS: u64 = 60;
+
+// 可订阅的事件
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+pub enum WebhookEvent {
+    #[serde(rename = "item.created")]
+    ItemCreated, // 提交了新的Hitokoto
+    #[serde(rename = "item.approved")]
+    ItemApproved, // Hitokoto通过审核
+    #[serde(rename = "item.deleted")]
+    ItemDeleted, // Hitokoto被拒绝或因举报隐藏
+    #[serde(rename = "collection.created")]
+    CollectionCreated, // 创建了新文集
+    #[serde(rename = "user.registered")]
+    UserRegistered, // 注册了新用户
+}
+
+impl WebhookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WebhookEvent::ItemCreated => "item.created",
+            WebhookEvent::ItemApproved => "item.approved",
+            WebhookEvent::ItemDeleted => "item.deleted",
+            WebhookEvent::CollectionCreated => "collection.created",
+            WebhookEvent::UserRegistered => "user.registered",
+        }
+    }
+}
+
+// 管理员注册的回调
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Webhook {
+    pub webhook_id: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>, // 订阅的事件
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub secret: String, // 用于计算签名的密钥，只在注册时返回
+    pub created_by: u32,
+    pub created_at: u64,
+}
+
+impl Webhook {
+    // 创建回调，secret 为空时随机生成
+    pub fn new(
+        url: String,
+        events: Vec<WebhookEvent>,
+        secret: Option<String>,
+        created_by: u32,
+    ) -> Self {
+        Webhook {
+            webhook_id: Uuid::new_v4().to_string(),
+            url,
+            events,
+            secret: secret.unwrap_or_else(|| {
+                format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+            }),
+            created_by,
+            created_at: now(),
+        }
+    }
+
+    // 去掉密钥的副本，用于列出和删除回调的响应
+    pub fn redacted(mut self) -> Self {
+        self.secret = String::new();
+        self
+    }
+}
+
+// 注册回调的请求，user_id 为操作者，省略 secret 时随机生成
+#[derive(Deserialize)]
+pub struct NewWebhookRequest {
+    pub user_id: u32,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub secret: Option<String>,
+}
+
+// 投递记录的查询参数
+#[derive(FromForm)]
+pub struct DeliveryLogQuery {
+    pub user_id: u32,
+    pub limit: Option<usize>, // 默认 50，最大 500
+}
+
+// 投递状态
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Pending,   // 等待投递或重试
+    Succeeded, // 对方返回 2xx
+    Failed,    // 重试次数用完或回调已删除
+}
+
+// 一次事件投递，保存在队列中直到成功或放弃
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Delivery {
+    pub delivery_id: String,
+    pub webhook_id: String,
+    pub event: WebhookEvent,
+    pub payload: Value, // 请求正文，每次重试都发送相同的内容
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+    pub last_status_code: Option<u16>, // 最近一次请求的 HTTP 状态码，连接失败时为空
+    pub last_error: Option<String>,
+    pub created_at: u64,
+    pub finished_at: Option<u64>,
+}
+
+// 用密钥计算正文的 HMAC-SHA256 签名，返回十六进制字符串
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC 接受任意长度的密钥");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+// 回调的注册表与投递队列
+// 事件入队后由后台任务投递，失败时按指数退避重试
+// persist 为 false 时只保存在内存中，load_data 会换成读写 webhook.json 和 webhook_delivery.json 的实例
+pub struct WebhookDispatcher {
+    webhooks: Mutex<HashMap<String, Webhook>>,
+    deliveries: Mutex<Vec<Delivery>>, // 按创建时间排列
+    config: WebhookConfig,
+    client: reqwest::Client,
+    wakeup: Notify,
+    persist: bool,
+}
+
+impl WebhookDispatcher {
+    pub fn new(config: WebhookConfig, persist: bool) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(concat!("pencil-api-webhook/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+        WebhookDispatcher {
+            webhooks: Mutex::new(HashMap::new()),
+            deliveries: Mutex::new(Vec::new()),
+            config,
+            client,
+            wakeup: Notify::new(),
+            persist,
+        }
+    }
+
+    // 从 webhook.json 和 webhook_delivery.json 加载回调和投递队列，文件不存在时为空
+    pub async fn load_from_files(&self) -> AppResult<()> {
+        if !self.persist {
+            return Ok(());
+        }
+        if let Ok(mut file) = File::open("webhook.json").await {
+            let mut contents = String::new();
+            file.read_to_string(&mut contents)
+                .await
+                .map_err(|e| AppError::Io(format!("无法读取回调数据文件: {}", e)))?;
+            if !contents.trim().is_empty() {
+                let webhooks: Vec<Webhook> = serde_json::from_str(&contents)
+                    .map_err(|e| AppError::Json(format!("回调数据文件格式错误: {}", e)))?;
+                let mut store = self.webhooks.lock().await;
+                for webhook in webhooks {
+                    store.insert(webhook.webhook_id.clone(), webhook);
+                }
+            }
+        }
+        if let Ok(mut file) = File::open("webhook_delivery.json").await {
+            let mut contents = String::new();
+            file.read_to_string(&mut contents)
+                .await
+                .map_err(|e| AppError::Io(format!("无法读取投递数据文件: {}", e)))?;
+            if !contents.trim().is_empty() {
+                *self.deliveries.lock().await = serde_json::from_str(&contents)
+                    .map_err(|e| AppError::Json(format!("投递数据文件格式错误: {}", e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn save_webhooks_to_file(&self) -> AppResult<()> {
+        if !self.persist {
+            return Ok(());
+        }
+        let webhooks = self.webhooks.lock().await;
+        let mut webhooks_vec: Vec<&Webhook> = webhooks.values().collect();
+        webhooks_vec.sort_by_key(|webhook| webhook.created_at);
+        let json = serde_json::to_string_pretty(&webhooks_vec)
+            .map_err(|e| AppError::Json(format!("序列化回调数据失败: {}", e)))?;
+        drop(webhooks);
+        write_file("webhook.json", &json).await
+    }
+
+    async fn save_deliveries_to_file(&self) -> AppResult<()> {
+        if !self.persist {
+            return Ok(());
+        }
+        let deliveries = self.deliveries.lock().await;
+        let json = serde_json::to_string_pretty(&*deliveries)
+            .map_err(|e| AppError::Json(format!("序列化投递数据失败: {}", e)))?;
+        drop(deliveries);
+        write_file("webhook_delivery.json", &json).await
+    }
+
+    // 注册回调
+    pub async fn register(&self, webhook: Webhook) {
+        self.webhooks
+            .lock()
+            .await
+            .insert(webhook.webhook_id.clone(), webhook);
+        if let Err(e) = self.save_webhooks_to_file().await {
+            eprintln!("保存回调数据到文件失败: {e}");
+        }
+    }
+
+    // 列出全部回调，按注册时间排列
+    pub async fn list(&self) -> Vec<Webhook> {
+        let mut webhooks: Vec<Webhook> = self.webhooks.lock().await.values().cloned().collect();
+        webhooks.sort_by_key(|webhook| webhook.created_at);
+        webhooks
+    }
+
+    pub async fn get(&self, webhook_id: &str) -> Option<Webhook> {
+        self.webhooks.lock().await.get(webhook_id).cloned()
+    }
+
+    // 删除回调，尚未投递的记录标记为失败
+    pub async fn remove(&self, webhook_id: &str) -> AppResult<Webhook> {
+        let webhook = self
+            .webhooks
+            .lock()
+            .await
+            .remove(webhook_id)
+            .ok_or_else(|| AppError::NotFound(format!("回调ID {} 不存在", webhook_id)))?;
+        let finished_at = now();
+        for delivery in self.deliveries.lock().await.iter_mut() {
+            if delivery.webhook_id == webhook_id && delivery.status == DeliveryStatus::Pending {
+                delivery.status = DeliveryStatus::Failed;
+                delivery.last_error = Some("回调已删除".to_string());
+                delivery.finished_at = Some(finished_at);
+            }
+        }
+        if let Err(e) = self.save_webhooks_to_file().await {
+            eprintln!("保存回调数据到文件失败: {e}");
+        }
+        if let Err(e) = self.save_deliveries_to_file().await {
+            eprintln!("保存投递数据到文件失败: {e}");
+        }
+        Ok(webhook)
+    }
+
+    // 为订阅了该事件的每个回调创建一条投递并唤醒投递任务，返回创建的投递数
+    pub async fn enqueue(&self, event: WebhookEvent, data: Value) -> usize {
+        let created_at = now();
+        let subscribers: Vec<String> = self
+            .webhooks
+            .lock()
+            .await
+            .values()
+            .filter(|webhook| webhook.events.contains(&event))
+            .map(|webhook| webhook.webhook_id.clone())
+            .collect();
+        if subscribers.is_empty() {
+            return 0;
+        }
+
+        let mut deliveries = self.deliveries.lock().await;
+        for webhook_id in &subscribers {
+            let delivery_id = Uuid::new_v4().to_string();
+            let payload = serde_json::json!({
+                "delivery_id": delivery_id,
+                "event": event,
+                "created_at": created_at,
+                "data": data,
+            });
+            deliveries.push(Delivery {
+                delivery_id,
+                webhook_id: webhook_id.clone(),
+                event,
+                payload,
+                status: DeliveryStatus::Pending,
+                attempts: 0,
+                next_attempt_at: created_at,
+                last_status_code: None,
+                last_error: None,
+                created_at,
+                finished_at: None,
+            });
+        }
+        drop(deliveries);
+
+        if let Err(e) = self.save_deliveries_to_file().await {
+            eprintln!("保存投递数据到文件失败: {e}");
+        }
+        self.wakeup.notify_one();
+        subscribers.len()
+    }
+
+    // 某个回调的投递记录，最新的在前
+    pub async fn deliveries(&self, webhook_id: &str, limit: usize) -> Vec<Delivery> {
+        self.deliveries
+            .lock()
+            .await
+            .iter()
+            .rev()
+            .filter(|delivery| delivery.webhook_id == webhook_id)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    // 第 attempts 次失败后等待的秒数：retry_base_secs 每次翻倍，不超过 retry_max_secs
+    fn retry_delay(&self, attempts: u32) -> u64 {
+        let factor = 1u64
+            .checked_shl(attempts.saturating_sub(1))
+            .unwrap_or(u64::MAX);
+        self.config
+            .retry_base_secs
+            .saturating_mul(factor)
+            .min(self.config.retry_max_secs)
+    }
+
+    // 发送一次请求，返回 HTTP 状态码和失败原因
+    async fn attempt(
+        &self,
+        webhook: &Webhook,
+        delivery: &Delivery,
+    ) -> (Option<u16>, Option<String>) {
+        let body = delivery.payload.to_string();
+        let request = self
+            .client
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header("X-Pencil-Event", delivery.event.as_str())
+            .header("X-Pencil-Delivery", &delivery.delivery_id)
+            .header(
+                "X-Pencil-Signature",
+                format!("sha256={}", sign(&webhook.secret, body.as_bytes())),
+            )
+            .body(body);
+        match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                (Some(response.status().as_u16()), None)
+            }
+            Ok(response) => (
+                Some(response.status().as_u16()),
+                Some(format!("HTTP {}", response.status())),
+            ),
+            Err(e) => (None, Some(e.to_string())),
+        }
+    }
+
+    // 投递所有到期的记录，now 为当前时间戳，返回尝试的投递数
+    // 不同回调的投递并发进行；同一回调按入队顺序依次投递，失败后本轮不再尝试该回调，
+    // 其余到期的投递按同样的退避时间推迟。因此一个无响应的地址每轮最多占用一次请求超时，
+    // 不会因积压的投递而长时间阻塞其他回调
+    // 只应由一个任务调用，请求期间不持有锁
+    pub async fn deliver_due(&self, now: u64) -> usize {
+        let due: Vec<Delivery> = self
+            .deliveries
+            .lock()
+            .await
+            .iter()
+            .filter(|delivery| {
+                delivery.status == DeliveryStatus::Pending && delivery.next_attempt_at <= now
+            })
+            .cloned()
+            .collect();
+
+        let mut groups: HashMap<&str, Vec<&Delivery>> = HashMap::new();
+        for delivery in &due {
+            groups
+                .entry(delivery.webhook_id.as_str())
+                .or_default()
+                .push(delivery);
+        }
+        let attempted: usize = join_all(groups.into_values().map(|group| async move {
+            for (i, delivery) in group.iter().enumerate() {
+                if let Some(retry_at) = self.deliver(delivery, now).await {
+                    self.postpone(&group[i + 1..], retry_at).await;
+                    return i + 1;
+                }
+            }
+            group.len()
+        }))
+        .await
+        .into_iter()
+        .sum();
+
+        if attempted > 0 {
+            self.prune().await;
+            if let Err(e) = self.save_deliveries_to_file().await {
+                eprintln!("保存投递数据到文件失败: {e}");
+            }
+        }
+        attempted
+    }
+
+    // 推迟尚未尝试的投递，不计入尝试次数
+    async fn postpone(&self, skipped: &[&Delivery], retry_at: u64) {
+        if skipped.is_empty() {
+            return;
+        }
+        let mut deliveries = self.deliveries.lock().await;
+        for record in deliveries.iter_mut().filter(|record| {
+            skipped
+                .iter()
+                .any(|delivery| delivery.delivery_id == record.delivery_id)
+        }) {
+            record.next_attempt_at = retry_at;
+        }
+    }
+
+    // 尝试一次投递并更新记录：成功或达到最大尝试次数时结束，否则安排重试
+    // 请求失败时返回按本次失败计算的下次尝试时间，回调已删除时返回 None
+    async fn deliver(&self, delivery: &Delivery, now: u64) -> Option<u64> {
+        let webhook = self.get(&delivery.webhook_id).await;
+        let (status_code, error) = match &webhook {
+            Some(webhook) => self.attempt(webhook, delivery).await,
+            None => (None, Some("回调已删除".to_string())),
+        };
+        let mut deliveries = self.deliveries.lock().await;
+        let record = deliveries
+            .iter_mut()
+            .find(|record| record.delivery_id == delivery.delivery_id)?;
+        record.attempts += 1;
+        record.last_status_code = status_code;
+        record.last_error = error
+            .as_ref()
+            .map(|error| error.chars().take(MAX_ERROR_LENGTH).collect());
+        let retry_at = now + self.retry_delay(record.attempts);
+        if error.is_none() {
+            record.status = DeliveryStatus::Succeeded;
+            record.finished_at = Some(now);
+            return None;
+        }
+        if record.attempts >= self.config.max_attempts || webhook.is_none() {
+            record.status = DeliveryStatus::Failed;
+            record.finished_at = Some(now);
+        } else {
+            record.next_attempt_at = retry_at;
+        }
+        webhook.is_some().then_some(retry_at)
+    }
+
+    // 已结束的投递记录只保留最近的 log_size 条，待投递的始终保留
+    async fn prune(&self) {
+        let mut deliveries = self.deliveries.lock().await;
+        let finished = deliveries
+            .iter()
+            .filter(|delivery| delivery.status != DeliveryStatus::Pending)
+            .count();
+        let mut excess = finished.saturating_sub(self.config.log_size);
+        deliveries.retain(|delivery| {
+            if excess > 0 && delivery.status != DeliveryStatus::Pending {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    // 距下一条待投递记录到期的时间，最长 MAX_IDLE_SECS 秒
+    async fn idle_duration(&self, now: u64) -> Duration {
+        let next = self
+            .deliveries
+            .lock()
+            .await
+            .iter()
+            .filter(|delivery| delivery.status == DeliveryStatus::Pending)
+            .map(|delivery| delivery.next_attempt_at)
+            .min();
+        let secs = next.map_or(MAX_IDLE_SECS, |next| {
+            next.saturating_sub(now).min(MAX_IDLE_SECS)
+        });
+        Duration::from_secs(secs)
+    }
+
+    // 后台投递任务：投递到期的记录，然后休眠到下一条到期或有新事件入队，直到服务关闭
+    pub async fn run(self: Arc<Self>, mut shutdown: rocket::Shutdown) {
+        loop {
+            self.deliver_due(now()).await;
+            let idle = self.idle_duration(now()).await;
+            tokio::select! {
+                _ = self.wakeup.notified() => {}
+                _ = tokio::time::sleep(idle) => {}
+                _ = &mut shutdown => break,
+            }
+        }
+    }
+}
+
+async fn write_file(path: &str, contents: &str) -> AppResult<()> {
+    let mut file = File::create(path)
+        .await
+        .map_err(|e| AppError::Io(format!("创建数据文件 {} 失败: {}", path, e)))?;
+    file.write_all(contents.as_bytes())
+        .await
+        .map_err(|e| AppError::Io(format!("写入数据文件 {} 失败: {}", path, e)))?;
+    file.flush()
+        .await
+        .map_err(|e| AppError::Io(format!("刷新数据文件 {} 失败: {}", path, e)))?;
+    Ok(())
+}
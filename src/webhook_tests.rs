@@ -0,0 +1,257 @@
+use crate::config::WebhookConfig;
+use crate::webhook::{Delivery, DeliveryStatus, Webhook, WebhookDispatcher, WebhookEvent, sign};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+
+// 本地 HTTP 替身收到的请求：请求头（小写名称）和正文
+type Received = Arc<Mutex<Vec<(HashMap<String, String>, String)>>>;
+
+// 启动本地 HTTP 替身，依次用 statuses 中的状态码应答，返回回调网址和收到的请求
+async fn spawn_receiver(statuses: Vec<u16>) -> (String, Received) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let received: Received = Arc::new(Mutex::new(Vec::new()));
+    let log = received.clone();
+    tokio::spawn(async move {
+        for status in statuses {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let (headers, body) = loop {
+                let read = socket.read(&mut chunk).await.unwrap();
+                buffer.extend_from_slice(&chunk[..read]);
+                let text = String::from_utf8_lossy(&buffer).to_string();
+                let Some(end) = text.find("\r\n\r\n") else {
+                    continue;
+                };
+                let headers: HashMap<String, String> = text[..end]
+                    .lines()
+                    .skip(1)
+                    .filter_map(|line| line.split_once(':'))
+                    .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+                    .collect();
+                let length: usize = headers["content-length"].parse().unwrap();
+                if text.len() >= end + 4 + length {
+                    break (headers, text[end + 4..end + 4 + length].to_string());
+                }
+            };
+            log.lock().await.push((headers, body));
+            let response = format!(
+                "HTTP/1.1 {} Test\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    (url, received)
+}
+
+// 启动只接受连接、从不应答的地址，返回回调网址
+async fn spawn_silent_receiver() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut sockets = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            sockets.push(socket);
+        }
+    });
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_signature() {
+        // 测试 HMAC-SHA256 签名（RFC 4231 测试用例 2）
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[rocket::async_test]
+    async fn test_webhook_delivery_retries_until_success() {
+        // 测试投递到本地 HTTP 替身：第一次返回 500 后按退避时间重试，第二次成功
+        let (url, received) = spawn_receiver(vec![500, 200]).await;
+        let dispatcher = WebhookDispatcher::new(WebhookConfig::default(), false);
+        let webhook = Webhook::new(
+            url,
+            vec![WebhookEvent::ItemCreated],
+            Some("secret".to_string()),
+            1,
+        );
+        let webhook_id = webhook.webhook_id.clone();
+        dispatcher.register(webhook).await;
+
+        // 只有订阅的事件会入队
+        assert_eq!(
+            dispatcher
+                .enqueue(
+                    WebhookEvent::CollectionCreated,
+                    serde_json::json!({ "collection": {} })
+                )
+                .await,
+            0
+        );
+        assert_eq!(
+            dispatcher
+                .enqueue(
+                    WebhookEvent::ItemCreated,
+                    serde_json::json!({ "item": { "hitokoto": "一言" } })
+                )
+                .await,
+            1
+        );
+
+        let start = dispatcher.deliveries(&webhook_id, 10).await[0].created_at;
+        assert_eq!(dispatcher.deliver_due(start).await, 1);
+        let delivery = dispatcher.deliveries(&webhook_id, 10).await.remove(0);
+        assert_eq!(delivery.status, DeliveryStatus::Pending);
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.last_status_code, Some(500));
+        assert_eq!(delivery.next_attempt_at, start + 10);
+
+        // 退避时间未到时不会重试
+        assert_eq!(dispatcher.deliver_due(start + 5).await, 0);
+        assert_eq!(dispatcher.deliver_due(start + 10).await, 1);
+        let delivery = dispatcher.deliveries(&webhook_id, 10).await.remove(0);
+        assert_eq!(delivery.status, DeliveryStatus::Succeeded);
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(delivery.last_status_code, Some(200));
+        assert_eq!(delivery.finished_at, Some(start + 10));
+
+        // 两次请求的正文相同，签名与事件头正确
+        let received = received.lock().await;
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].1, received[1].1);
+        let (headers, body) = &received[1];
+        assert_eq!(
+            headers["x-pencil-signature"],
+            format!("sha256={}", sign("secret", body.as_bytes()))
+        );
+        assert_eq!(headers["x-pencil-event"], "item.created");
+        assert_eq!(headers["x-pencil-delivery"], delivery.delivery_id);
+        let payload: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(payload["event"], "item.created");
+        assert_eq!(payload["data"]["item"]["hitokoto"], "一言");
+    }
+
+    #[rocket::async_test]
+    async fn test_webhook_deliveries_run_concurrently() {
+        // 测试无响应的地址只拖慢自己的投递：两个超时的回调并发等待，正常的回调照常成功
+        let config = WebhookConfig {
+            timeout_secs: 1,
+            ..WebhookConfig::default()
+        };
+        let dispatcher = WebhookDispatcher::new(config, false);
+        let (url, _received) = spawn_receiver(vec![200]).await;
+        let mut urls = vec![url];
+        urls.push(spawn_silent_receiver().await);
+        urls.push(spawn_silent_receiver().await);
+        let mut ids = Vec::new();
+        for url in urls {
+            let webhook = Webhook::new(url, vec![WebhookEvent::UserRegistered], None, 1);
+            ids.push(webhook.webhook_id.clone());
+            dispatcher.register(webhook).await;
+        }
+        dispatcher
+            .enqueue(
+                WebhookEvent::UserRegistered,
+                serde_json::json!({ "user": {} }),
+            )
+            .await;
+
+        let now = dispatcher.deliveries(&ids[0], 10).await[0].created_at;
+        let started = std::time::Instant::now();
+        assert_eq!(dispatcher.deliver_due(now).await, 3);
+        assert!(started.elapsed() < std::time::Duration::from_millis(1900));
+
+        let status = |delivery: Vec<Delivery>| delivery[0].status;
+        assert_eq!(
+            status(dispatcher.deliveries(&ids[0], 10).await),
+            DeliveryStatus::Succeeded
+        );
+        assert_eq!(
+            status(dispatcher.deliveries(&ids[1], 10).await),
+            DeliveryStatus::Pending
+        );
+    }
+
+    #[rocket::async_test]
+    async fn test_webhook_backlog_does_not_block_dispatch() {
+        // 测试无响应的地址积压多条投递时，本轮只尝试一次，其余按同样的退避时间推迟，正常的回调全部成功
+        let config = WebhookConfig {
+            timeout_secs: 1,
+            ..WebhookConfig::default()
+        };
+        let dispatcher = WebhookDispatcher::new(config, false);
+        let (healthy_url, received) = spawn_receiver(vec![200; 3]).await;
+        let healthy = Webhook::new(healthy_url, vec![WebhookEvent::UserRegistered], None, 1);
+        let silent = Webhook::new(
+            spawn_silent_receiver().await,
+            vec![WebhookEvent::UserRegistered],
+            None,
+            1,
+        );
+        let (healthy_id, silent_id) = (healthy.webhook_id.clone(), silent.webhook_id.clone());
+        dispatcher.register(healthy).await;
+        dispatcher.register(silent).await;
+        for _ in 0..3 {
+            dispatcher
+                .enqueue(
+                    WebhookEvent::UserRegistered,
+                    serde_json::json!({ "user": {} }),
+                )
+                .await;
+        }
+
+        let now = dispatcher.deliveries(&healthy_id, 10).await[0].created_at;
+        let started = std::time::Instant::now();
+        assert_eq!(dispatcher.deliver_due(now).await, 4);
+        assert!(started.elapsed() < std::time::Duration::from_millis(1900));
+        assert_eq!(received.lock().await.len(), 3);
+
+        assert!(
+            dispatcher
+                .deliveries(&healthy_id, 10)
+                .await
+                .iter()
+                .all(|delivery| delivery.status == DeliveryStatus::Succeeded)
+        );
+        // 最早的一条尝试后失败，其余两条未尝试，与它一起推迟
+        let backlog = dispatcher.deliveries(&silent_id, 10).await;
+        let attempts: Vec<u32> = backlog.iter().map(|delivery| delivery.attempts).collect();
+        assert_eq!(attempts, vec![0, 0, 1]);
+        assert!(backlog.iter().all(|delivery| {
+            delivery.status == DeliveryStatus::Pending && delivery.next_attempt_at == now + 10
+        }));
+        assert_eq!(dispatcher.deliver_due(now + 5).await, 0);
+    }
+
+    #[test]
+    fn test_webhook_redacted_hides_secret() {
+        // 测试列出回调时不返回密钥
+        let webhook = Webhook::new(
+            "http://localhost/hook".to_string(),
+            vec![WebhookEvent::ItemCreated],
+            None,
+            1,
+        );
+        assert_eq!(
+            serde_json::to_value(&webhook).unwrap()["secret"]
+                .as_str()
+                .unwrap()
+                .len(),
+            64
+        );
+        let redacted = serde_json::to_value(webhook.redacted()).unwrap();
+        assert!(redacted.get("secret").is_none());
+        assert!(redacted.get("webhook_id").is_some());
+    }
+}